@@ -1,33 +1,226 @@
-use crate::components::editor::Editor;
+use crate::components::command_palette::CommandPalette;
+use crate::components::daily_calendar::DailyCalendar;
+use crate::components::editor::{editor_position, place_cursor, restore_position, Editor};
+use crate::components::find_replace::FindReplace;
+use crate::components::graph_view::GraphView;
+use crate::components::keymap_settings::{shortcut_from_event, KeymapSettings};
 use crate::components::outline::Outline;
 use crate::components::preview::Preview;
-use crate::components::quick_switcher::QuickSwitcher;
+use crate::components::quick_switcher::{find_headings, QuickSwitcher};
 use crate::components::sidebar::Sidebar;
-use crate::components::status_bar::StatusBar;
+use crate::components::status_bar::{StatusBar, Timer};
+use crate::components::tab_bar::TabBar;
 use crate::components::toast::Toast;
-use crate::state::{AppState, SaveStatus};
+use crate::markdown::{extract_headings, render_markdown, section_at, shift_section};
+use crate::state::commands::{Command, KeyContext};
+use crate::state::daily::today;
+use crate::state::replace::ReplaceQuery;
+use crate::state::{AppState, DailyNotesSettings, Keymap, NoteGraph, Pane, SaveStatus, ViewMode, Vim};
+use chrono::NaiveDate;
 use dioxus::document::eval;
 use dioxus::prelude::*;
+use std::collections::HashSet;
 use std::time::Duration;
 
+const MIN_FONT_SIZE: u8 = 14;
+const MAX_FONT_SIZE: u8 = 32;
+
+// Narrowest share of the editor either split pane can be dragged to
+const MIN_SPLIT_RATIO: f64 = 0.2;
+
+const PANES_RECT_JS: &str = r#"
+    const panes = document.querySelector('.editor-panes');
+    if (!panes) return null;
+    const rect = panes.getBoundingClientRect();
+    return [rect.left, rect.width];
+"#;
+
+/// Tab changes that leave the editor, so its position is saved first.
+#[derive(Clone, PartialEq)]
+enum TabAction {
+    Open(String),
+    OpenInNewTab(String),
+    Switch(usize),
+    Cycle(isize),
+    Close(usize),
+}
+
 #[component]
 pub fn App() -> Element {
     let mut state = use_signal(AppState::new);
     let mut debounce_timer = use_signal(|| 0u32);
     let mut is_quick_switcher_open = use_signal(|| false);
+    let mut quick_switcher_query = use_signal(String::new);
+    let mut sidebar_query = use_signal(String::new);
+    let mut is_graph_open = use_signal(|| false);
+    let mut is_calendar_open = use_signal(|| false);
+    let mut is_command_palette_open = use_signal(|| false);
+    let mut is_keymap_open = use_signal(|| false);
+    let mut is_find_replace_open = use_signal(|| false);
+    let mut replace_query = use_signal(ReplaceQuery::default);
     let mut toast_timer = use_signal(|| 0u32);
-    let mut jump_to_line = use_signal(|| None::<usize>);
+    let mut jump_to_line = use_signal(|| None::<(Pane, usize)>);
+    // Line of the caret or of the text being read, for the outline
+    let mut section_line = use_signal(|| None::<(Pane, usize)>);
     let mut font_size = use_signal(|| 20u8);
+    let mut timer = use_signal(Timer::default);
+    let mut vim = use_signal(Vim::default);
+    let mut split_vim = use_signal(Vim::default);
+    // Left pane's share of the width, and the panes' left edge and width while the divider is dragged
+    let mut split_ratio = use_signal(|| 0.5f64);
+    let mut divider_drag = use_signal(|| None::<(f64, f64)>);
+
+    // Put the caret where a template's {{cursor}} marker was
+    let focus_new_note = move |cursor: Option<usize>| {
+        if let (Some(offset), Some(note)) = (cursor, (state.read().current_note)()) {
+            place_cursor(&note.content, offset);
+        }
+    };
+
+    // Remember where the editor was in the tab being left, and put the
+    // caret back where it was in the tab shown next
+    use_effect(move || {
+        if let Some((selection, scroll_top)) = state.peek().active_tab_position() {
+            restore_position(selection, scroll_top);
+        }
+    });
+    let change_tab = use_callback(move |action: TabAction| {
+        spawn(async move {
+            if let Some((selection, scroll_top)) = editor_position().await {
+                state.write().set_tab_position(selection, scroll_top);
+            }
+            match action {
+                TabAction::Open(id) => state.write().select_note(&id),
+                TabAction::OpenInNewTab(id) => state.write().open_in_new_tab(&id),
+                TabAction::Switch(index) => state.write().switch_tab(index),
+                TabAction::Cycle(step) => state.write().cycle_tab(step),
+                TabAction::Close(index) => state.write().close_tab(index),
+            }
+            if let Some((selection, scroll_top)) = state.read().active_tab_position() {
+                restore_position(selection, scroll_top);
+            }
+        });
+    });
+
+    // Every shortcut, button and palette entry runs through here
+    let run_command = use_callback(move |command: Command| match command {
+        Command::NewNote => {
+            let cursor = state.write().create_default_note();
+            focus_new_note(cursor);
+        }
+        Command::NewNoteFromTemplate => {
+            quick_switcher_query.set(String::from("+"));
+            is_quick_switcher_open.set(true);
+        }
+        Command::QuickSwitcher => {
+            quick_switcher_query.set(String::new());
+            is_quick_switcher_open.set(!is_quick_switcher_open());
+        }
+        Command::CommandPalette => is_command_palette_open.set(!is_command_palette_open()),
+        Command::NextTab => change_tab.call(TabAction::Cycle(1)),
+        Command::PreviousTab => change_tab.call(TabAction::Cycle(-1)),
+        Command::CloseTab => change_tab.call(TabAction::Close((state.read().active_tab)())),
+        Command::OpenKeybindings => is_keymap_open.set(!is_keymap_open()),
+        Command::FindReplace => is_find_replace_open.set(!is_find_replace_open()),
+        Command::ToggleSidebar => state.write().toggle_sidebar(),
+        Command::TogglePreview => state.write().toggle_preview(),
+        Command::ToggleHybrid => state.write().toggle_hybrid(),
+        Command::ToggleSplit => {
+            state.write().toggle_split();
+            split_vim.set(Vim::default());
+        }
+        Command::ToggleFocusMode => state.write().toggle_focus_mode(),
+        Command::ToggleVimMode => {
+            state.write().toggle_vim_mode();
+            vim.set(Vim::default());
+            split_vim.set(Vim::default());
+        }
+        Command::OpenToday => {
+            let cursor = state.write().open_daily_note(today());
+            focus_new_note(cursor);
+        }
+        Command::PreviousDailyNote => {
+            let cursor = state.write().open_adjacent_daily_note(-1);
+            focus_new_note(cursor);
+        }
+        Command::NextDailyNote => {
+            let cursor = state.write().open_adjacent_daily_note(1);
+            focus_new_note(cursor);
+        }
+        Command::ToggleCalendar => is_calendar_open.set(!is_calendar_open()),
+        Command::ToggleGraph => is_graph_open.set(!is_graph_open()),
+        Command::TogglePinned => state.write().toggle_pinned(),
+        Command::ToggleTemplate => state.write().toggle_template(),
+        Command::PromoteSection | Command::DemoteSection => {
+            // The heading of the section the caret is in, with its subsections
+            let pane = (state.read().focused_pane)();
+            let Some(note) = state.read().pane_note(pane) else { return };
+            let Some((_, line)) = section_line().filter(|(section_pane, _)| *section_pane == pane) else {
+                return;
+            };
+            let headings = extract_headings(&note.content);
+            let delta = if command == Command::PromoteSection { -1 } else { 1 };
+            let shifted = section_at(&headings, line).and_then(|i| shift_section(&note.content, &headings, i, delta));
+            if let Some(content) = shifted {
+                state.write().update_pane_content(pane, content);
+            }
+        }
+        Command::DeleteNote => state.write().delete_current_note(),
+        Command::IncreaseFontSize | Command::DecreaseFontSize => {
+            let size = if command == Command::IncreaseFontSize {
+                (font_size() + 2).min(MAX_FONT_SIZE)
+            } else {
+                font_size().saturating_sub(2).max(MIN_FONT_SIZE)
+            };
+            font_size.set(size);
+            let js = format!(
+                "document.documentElement.style.setProperty('--font-size-editor', '{}px')",
+                size
+            );
+            eval(&js);
+        }
+        Command::StartPauseTimer => timer.write().start_or_pause(),
+        Command::ResetTimer => timer.write().reset(),
+        Command::Timer5 | Command::Timer15 | Command::Timer25 | Command::Timer45 => {
+            if let Some(seconds) = command.timer_preset() {
+                timer.write().set_duration(seconds);
+            }
+        }
+        Command::ExportMarkdown | Command::ExportHtml => {
+            if let Some(note) = (state.read().current_note)() {
+                if command == Command::ExportHtml {
+                    let html = format!(
+                        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+                        escape_html(&note.title),
+                        render_markdown(&note.content)
+                    );
+                    download_file(&note.title, "html", "text/html", &html);
+                } else {
+                    download_file(&note.title, "md", "text/markdown", &note.content);
+                }
+            }
+        }
+        // Formatting runs in the editor, which owns the selection
+        Command::FindInNote
+        | Command::Bold
+        | Command::Italic
+        | Command::InlineCode
+        | Command::Strikethrough
+        | Command::InsertLink
+        | Command::FoldSection
+        | Command::UnfoldSection => {}
+    });
 
     // Auto-save effect with debounce
     let save_status = (state.read().save_status)();
+    let split_save_status = (state.read().split_save_status)();
     use_effect(move || {
-        if save_status == SaveStatus::Modified {
+        if save_status == SaveStatus::Modified || split_save_status == SaveStatus::Modified {
             let timer_id = debounce_timer();
             spawn(async move {
                 async_std::task::sleep(Duration::from_secs(1)).await;
                 if debounce_timer() == timer_id {
-                    state.write().save_current_note();
+                    state.write().save_open_notes();
                 }
             });
             debounce_timer.set(timer_id.wrapping_add(1));
@@ -50,13 +243,65 @@ pub fn App() -> Element {
         }
     });
 
+    let sidebar_results = use_memo(move || state.read().search(&sidebar_query()));
+    let smart_folders = use_memo(move || state.read().smart_folders());
+    let quick_switcher_results = use_memo(move || {
+        let query = quick_switcher_query();
+        if is_quick_switcher_open() && !query.starts_with('+') {
+            state.read().search(&query)
+        } else {
+            Ok(Vec::new())
+        }
+    });
+    let heading_results = use_memo(move || {
+        let query = quick_switcher_query();
+        if is_quick_switcher_open() && query.contains('#') && !query.starts_with('+') {
+            let state = state.read();
+            find_headings(&(state.notes)(), &query, |id| state.note_content(id))
+        } else {
+            Vec::new()
+        }
+    });
+
+    let replace_results = use_memo(move || {
+        if is_find_replace_open() {
+            state.read().find_in_notes(&replace_query())
+        } else {
+            Ok(Vec::new())
+        }
+    });
+
+    // Only load every note for the graph while it is open
+    let note_graph = use_memo(move || {
+        if is_graph_open() {
+            state.read().note_graph()
+        } else {
+            NoteGraph::default()
+        }
+    });
+
     let state_read = state.read();
     let notes = (state_read.notes)();
     let current_note = (state_read.current_note)();
     let is_sidebar_visible = (state_read.is_sidebar_visible)();
-    let is_preview_visible = (state_read.is_preview_visible)();
+    let view_mode = (state_read.view_mode)();
     let is_focus_mode = (state_read.is_focus_mode)();
     let save_status = (state_read.save_status)();
+    let backlinks = (state_read.backlinks)();
+    let settings = (state_read.settings)();
+    let keymap = (state_read.keymap)();
+    let can_undo_replace = !(state_read.replaced_notes)().is_empty();
+    let daily_settings = settings.daily_notes.clone();
+    let default_template = settings.default_template.clone();
+    let vim_mode = settings.vim_mode;
+    let templates = state_read.templates();
+    let daily_date = state_read.current_daily_date();
+    let tab_labels = state_read.tab_labels();
+    let active_tab = (state_read.active_tab)();
+    let split_note = state_read.pane_note(Pane::Secondary);
+    let split_save_status = state_read.pane_save_status(Pane::Secondary);
+    let focused_pane = (state_read.focused_pane)();
+    let folders = state_read.folders();
     drop(state_read);
 
     let current_id = current_note.as_ref().map(|n| n.id.clone());
@@ -65,6 +310,47 @@ pub fn App() -> Element {
         .map(|n| n.content.clone())
         .unwrap_or_default();
     let has_note = current_note.is_some();
+    let is_split = split_note.is_some();
+
+    // The outline, preview, notes list and status bar follow the focused pane
+    let focused_note = match (focused_pane, &split_note) {
+        (Pane::Secondary, Some(note)) => Some(note.clone()),
+        _ => current_note.clone(),
+    };
+    let focused_id = focused_note.as_ref().map(|n| n.id.clone());
+    let focused_content = focused_note
+        .as_ref()
+        .map(|n| n.content.clone())
+        .unwrap_or_default();
+    // Each pane's Vim undo history and selection belong to the note it shows
+    let split_id = split_note.as_ref().map(|n| n.id.clone());
+    use_effect({
+        let current_id = current_id.clone();
+        use_reactive!(|current_id| {
+            let _ = current_id;
+            vim.write().switch_note();
+        })
+    });
+    use_effect(use_reactive!(|split_id| {
+        let _ = split_id;
+        split_vim.write().switch_note();
+    }));
+    let focused_vim = if focused_pane == Pane::Secondary { split_vim } else { vim };
+    let primary_flex = if is_split { split_ratio() } else { 1.0 };
+
+    let is_available = move |command: Command| match command {
+        Command::PreviousDailyNote | Command::NextDailyNote => daily_date.is_some(),
+        command => has_note || !command.needs_note(),
+    };
+    let palette_commands: Vec<Command> = Command::ALL
+        .iter()
+        .copied()
+        .filter(|command| {
+            *command != Command::CommandPalette
+                && command.context() == KeyContext::Global
+                && is_available(*command)
+        })
+        .collect();
 
     let app_class = if is_focus_mode {
         "app-container focus-mode"
@@ -76,36 +362,17 @@ pub fn App() -> Element {
         div {
             class: "{app_class}",
             tabindex: "0",
-            onkeydown: move |evt| {
-                if evt.modifiers().ctrl() || evt.modifiers().meta() {
-                    if evt.modifiers().shift() {
-                        match evt.key() {
-                            Key::Character(c) if c == "F" || c == "f" => {
-                                evt.prevent_default();
-                                state.write().toggle_focus_mode();
-                            }
-                            _ => {}
-                        }
-                    } else {
-                        match evt.key() {
-                            Key::Character(c) if c == "n" => {
-                                evt.prevent_default();
-                                state.write().create_note();
-                            }
-                            Key::Character(c) if c == "b" => {
-                                evt.prevent_default();
-                                state.write().toggle_sidebar();
-                            }
-                            Key::Character(c) if c == "p" => {
-                                evt.prevent_default();
-                                state.write().toggle_preview();
-                            }
-                            Key::Character(c) if c == "k" => {
-                                evt.prevent_default();
-                                is_quick_switcher_open.set(!is_quick_switcher_open());
-                            }
-                            _ => {}
-                        }
+            onkeydown: {
+                let keymap = keymap.clone();
+                move |evt: KeyboardEvent| {
+                    // Editor bindings were already handled before bubbling here
+                    let shortcut = shortcut_from_event(&evt);
+                    let command = keymap
+                        .lookup(&shortcut, KeyContext::Global)
+                        .filter(|c| is_available(*c));
+                    if let Some(command) = command {
+                        evt.prevent_default();
+                        run_command.call(command);
                     }
                 }
             },
@@ -114,9 +381,11 @@ pub fn App() -> Element {
                 // Left: Document outline (always visible when note is open)
                 if has_note {
                     Outline {
-                        content: content.clone(),
+                        content: focused_content.clone(),
+                        current_line: section_line().filter(|(pane, _)| *pane == focused_pane).map(|(_, line)| line),
+                        on_change: move |content: String| state.write().update_pane_content(focused_pane, content),
                         on_jump: move |line: usize| {
-                            jump_to_line.set(Some(line));
+                            jump_to_line.set(Some((focused_pane, line)));
                         },
                     }
                 }
@@ -124,17 +393,110 @@ pub fn App() -> Element {
                 // Center: Editor (and preview if enabled)
                 if has_note {
                     div { class: "editor-area",
-                        Editor {
-                            content: content.clone(),
-                            save_status,
-                            on_change: move |new_content: String| {
-                                state.write().update_content(new_content);
-                            },
-                            jump_to_line: jump_to_line(),
+                        TabBar {
+                            tabs: tab_labels.clone(),
+                            active: active_tab,
+                            on_select: move |index: usize| change_tab.call(TabAction::Switch(index)),
+                            on_close: move |index: usize| change_tab.call(TabAction::Close(index)),
+                            on_move: move |(from, to): (usize, usize)| state.write().move_tab(from, to),
                         }
+                        div {
+                            class: "editor-panes",
+                            class: if divider_drag().is_some() { "editor-panes-resizing" } else { "" },
+                            onmousemove: move |evt| {
+                                if let Some((left, width)) = divider_drag() {
+                                    let ratio = (evt.client_coordinates().x - left) / width;
+                                    split_ratio.set(ratio.clamp(MIN_SPLIT_RATIO, 1.0 - MIN_SPLIT_RATIO));
+                                }
+                            },
+                            onmouseup: move |_| divider_drag.set(None),
+                            onmouseleave: move |_| divider_drag.set(None),
+                            div {
+                                class: "split-pane",
+                                class: if is_split && focused_pane == Pane::Primary { "split-pane-focused" } else { "" },
+                                style: "flex: {primary_flex}",
+                                onfocusin: move |_| state.write().focus_pane(Pane::Primary),
+                                Editor {
+                                    content: content.clone(),
+                                    save_status,
+                                    on_change: move |new_content: String| {
+                                        state.write().update_content(new_content);
+                                    },
+                                    keymap: keymap.clone(),
+                                    is_hybrid: view_mode == ViewMode::Hybrid,
+                                    vim: vim_mode.then_some(vim),
+                                    on_save: move |_| state.write().save_current_note(),
+                                    jump_to_line: Some(jump_to_line),
+                                    note_id: current_id.clone().unwrap_or_default(),
+                                    on_cursor_line: move |line: usize| section_line.set(Some((Pane::Primary, line))),
+                                    title: current_note.as_ref().map(|n| n.title.clone()).unwrap_or_default(),
+                                    has_custom_title: current_note.as_ref().is_some_and(|n| n.custom_title.is_some()),
+                                    on_title_change: move |title: Option<String>| {
+                                        state.write().set_custom_title(title);
+                                    },
+                                    folder: current_note.as_ref().and_then(|n| n.folder.clone()).unwrap_or_default(),
+                                    folders: folders.clone(),
+                                    on_folder_change: move |folder: Option<String>| {
+                                        state.write().set_pane_folder(Pane::Primary, folder);
+                                    },
+                                }
+                            }
+
+                            if let Some(split_note) = split_note.clone() {
+                                div {
+                                    class: "split-divider",
+                                    title: "Drag to resize",
+                                    onmousedown: move |evt| {
+                                        evt.prevent_default();
+                                        spawn(async move {
+                                            let rect = eval(PANES_RECT_JS).join::<Option<(f64, f64)>>().await;
+                                            divider_drag.set(rect.ok().flatten().filter(|(_, width)| *width > 0.0));
+                                        });
+                                    },
+                                    ondoubleclick: move |_| split_ratio.set(0.5),
+                                }
+                                div {
+                                    class: "split-pane",
+                                    class: if focused_pane == Pane::Secondary { "split-pane-focused" } else { "" },
+                                    style: "flex: {1.0 - primary_flex}",
+                                    onfocusin: move |_| state.write().focus_pane(Pane::Secondary),
+                                    button {
+                                        class: "split-close",
+                                        onclick: move |_| state.write().close_split(),
+                                        title: "Close split",
+                                        "×"
+                                    }
+                                    Editor {
+                                        pane: Pane::Secondary,
+                                        content: split_note.content.clone(),
+                                        save_status: split_save_status,
+                                        on_change: move |new_content: String| {
+                                            state.write().update_pane_content(Pane::Secondary, new_content);
+                                        },
+                                        keymap: keymap.clone(),
+                                        is_hybrid: view_mode == ViewMode::Hybrid,
+                                        vim: vim_mode.then_some(split_vim),
+                                        on_save: move |_| state.write().save_pane(Pane::Secondary),
+                                        jump_to_line: Some(jump_to_line),
+                                        note_id: split_note.id.clone(),
+                                        on_cursor_line: move |line: usize| section_line.set(Some((Pane::Secondary, line))),
+                                        title: split_note.title.clone(),
+                                        has_custom_title: split_note.custom_title.is_some(),
+                                        on_title_change: move |title: Option<String>| {
+                                            state.write().set_pane_title(Pane::Secondary, title);
+                                        },
+                                        folder: split_note.folder.clone().unwrap_or_default(),
+                                        folders: folders.clone(),
+                                        on_folder_change: move |folder: Option<String>| {
+                                            state.write().set_pane_folder(Pane::Secondary, folder);
+                                        },
+                                    }
+                                }
+                            }
 
-                        if is_preview_visible {
-                            Preview { content: content.clone() }
+                            if view_mode == ViewMode::Preview {
+                                Preview { content: focused_content.clone() }
+                            }
                         }
                     }
                 } else {
@@ -148,6 +510,10 @@ pub fn App() -> Element {
                             strong { "+" }
                             " to create a new note"
                         }
+                        p { class: "hint",
+                            kbd { "Ctrl+Shift+P" }
+                            " lists every command"
+                        }
                     }
                 }
 
@@ -155,47 +521,176 @@ pub fn App() -> Element {
                 if is_sidebar_visible {
                     Sidebar {
                         notes: notes.clone(),
-                        current_id: current_id.clone(),
-                        on_select: move |id: String| state.write().select_note(&id),
-                        on_new: move |_| state.write().create_note(),
+                        current_id: focused_id.clone(),
+                        on_select: move |id: String| {
+                            // A note picked while the right pane has focus opens there
+                            if is_split && focused_pane == Pane::Secondary {
+                                state.write().open_in_split(&id);
+                            } else {
+                                change_tab.call(TabAction::Open(id));
+                            }
+                        },
+                        on_open_in_tab: move |id: String| change_tab.call(TabAction::OpenInNewTab(id)),
+                        on_new: move |_| {
+                            let cursor = state.write().create_default_note();
+                            focus_new_note(cursor);
+                        },
+                        search_query: sidebar_query(),
+                        search_results: sidebar_results().unwrap_or_default(),
+                        search_error: sidebar_results().err(),
+                        on_search_change: move |query: String| sidebar_query.set(query),
+                        on_open_result: move |(id, line): (String, usize)| {
+                            state.write().select_note(&id);
+                            jump_to_line.set(Some((Pane::Primary, line)));
+                        },
+                        templates: templates.clone(),
+                        default_template: default_template.clone(),
+                        on_new_from_template: move |id: String| {
+                            let cursor = state.write().create_note_from_template(&id);
+                            focus_new_note(cursor);
+                        },
+                        on_set_default_template: move |id: Option<String>| {
+                            state.write().set_default_template(id);
+                        },
+                        smart_folders: smart_folders(),
+                        on_save_search: move |name: String| {
+                            let query = sidebar_query();
+                            state.write().save_search(&name, &query);
+                            sidebar_query.set(String::new());
+                        },
+                        on_delete_search: move |name: String| state.write().delete_saved_search(&name),
+                        backlinks: backlinks.clone(),
+                        on_open_backlink: move |(id, line): (String, usize)| {
+                            state.write().select_note(&id);
+                            jump_to_line.set(Some((Pane::Primary, line)));
+                        },
                     }
                 }
             }
 
             // Bottom: Unified control bar
             StatusBar {
-                content: if has_note { Some(content.clone()) } else { None },
+                content: if has_note { Some(focused_content.clone()) } else { None },
                 has_note,
                 is_sidebar_visible,
-                is_preview_visible,
+                is_preview_visible: view_mode == ViewMode::Preview,
+                is_hybrid: view_mode == ViewMode::Hybrid,
                 is_focus_mode,
-                font_size: font_size(),
-                on_toggle_sidebar: move |_| state.write().toggle_sidebar(),
-                on_toggle_preview: move |_| state.write().toggle_preview(),
-                on_toggle_focus: move |_| state.write().toggle_focus_mode(),
-                on_delete: move |_| state.write().delete_current_note(),
-                on_font_size_change: move |size: u8| {
-                    font_size.set(size);
-                    let js = format!(
-                        "document.documentElement.style.setProperty('--font-size-editor', '{}px')",
-                        size
-                    );
-                    eval(&js);
-                },
+                vim_status: vim_mode.then(|| focused_vim.read().status()),
+                is_template: current_note.as_ref().is_some_and(|n| n.is_template),
+                is_pinned: current_note.as_ref().is_some_and(|n| n.pinned),
+                timer,
+                keymap: keymap.clone(),
+                on_command: run_command,
+            }
+
+            // Command palette modal
+            if is_command_palette_open() {
+                CommandPalette {
+                    commands: palette_commands.clone(),
+                    keymap: keymap.clone(),
+                    on_run: move |command: Command| {
+                        is_command_palette_open.set(false);
+                        run_command.call(command);
+                    },
+                    on_close: move |_| is_command_palette_open.set(false),
+                }
             }
 
             // Quick Switcher modal
             if is_quick_switcher_open() {
                 QuickSwitcher {
                     notes: notes.clone(),
+                    templates: templates.clone(),
+                    initial_query: quick_switcher_query(),
+                    content_results: quick_switcher_results().unwrap_or_default(),
+                    search_error: quick_switcher_results().err(),
+                    heading_results: heading_results(),
+                    on_query_change: move |query: String| quick_switcher_query.set(query),
+                    on_open_result: move |(id, line): (String, usize)| {
+                        state.write().select_note(&id);
+                        jump_to_line.set(Some((Pane::Primary, line)));
+                        is_quick_switcher_open.set(false);
+                    },
+                    on_new_from_template: move |id: String| {
+                        let cursor = state.write().create_note_from_template(&id);
+                        focus_new_note(cursor);
+                        is_quick_switcher_open.set(false);
+                    },
                     on_select: move |id: String| {
                         state.write().select_note(&id);
                         is_quick_switcher_open.set(false);
                     },
+                    on_create: move |title: String| {
+                        let cursor = state.write().create_note_titled(&title);
+                        focus_new_note(cursor);
+                        is_quick_switcher_open.set(false);
+                    },
                     on_close: move |_| is_quick_switcher_open.set(false),
                 }
             }
 
+            // Keybinding settings
+            if is_keymap_open() {
+                KeymapSettings {
+                    keymap: keymap.clone(),
+                    on_change: move |keymap: Keymap| state.write().update_keymap(keymap),
+                    on_close: move |_| is_keymap_open.set(false),
+                }
+            }
+
+            // Vault-wide find and replace
+            if is_find_replace_open() {
+                FindReplace {
+                    query: replace_query(),
+                    results: replace_results().unwrap_or_default(),
+                    error: replace_results().err(),
+                    can_undo: can_undo_replace,
+                    on_query_change: move |query: ReplaceQuery| replace_query.set(query),
+                    on_apply: move |excluded: HashSet<(String, usize)>| {
+                        state.write().replace_in_notes(&replace_query(), &excluded);
+                    },
+                    on_undo: move |_| state.write().undo_replace(),
+                    on_open_match: move |(id, line): (String, usize)| {
+                        state.write().select_note(&id);
+                        jump_to_line.set(Some((Pane::Primary, line)));
+                        is_find_replace_open.set(false);
+                    },
+                    on_close: move |_| is_find_replace_open.set(false),
+                }
+            }
+
+            // Note graph modal
+            if is_graph_open() {
+                GraphView {
+                    graph: note_graph(),
+                    current_id: current_id.clone(),
+                    on_open: move |id: String| {
+                        state.write().select_note(&id);
+                        is_graph_open.set(false);
+                    },
+                    on_close: move |_| is_graph_open.set(false),
+                }
+            }
+
+            // Daily notes calendar
+            if is_calendar_open() {
+                DailyCalendar {
+                    notes: notes.clone(),
+                    settings: daily_settings.clone(),
+                    selected_date: daily_date,
+                    on_open_date: move |date: NaiveDate| {
+                        let cursor = state.write().open_daily_note(date);
+                        focus_new_note(cursor);
+                        is_calendar_open.set(false);
+                    },
+                    on_settings_change: move |settings: DailyNotesSettings| {
+                        state.write().update_daily_notes_settings(settings);
+                    },
+                    on_close: move |_| is_calendar_open.set(false),
+                }
+            }
+
             // Toast notification for deleted note
             if deleted_note.is_some() {
                 Toast {
@@ -211,3 +706,62 @@ pub fn App() -> Element {
         }
     }
 }
+
+/// File name for exporting a note: its title with characters file systems
+/// reject replaced.
+fn export_file_name(title: &str, extension: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.{}", name.trim(), extension)
+}
+
+/// Save `contents` where the user picks in the native save dialog.
+#[cfg(not(target_arch = "wasm32"))]
+fn download_file(title: &str, extension: &str, _mime: &str, contents: &str) {
+    let file_name = export_file_name(title, extension);
+    let extension = extension.to_string();
+    let contents = contents.to_string();
+    spawn(async move {
+        let dialog = rfd::AsyncFileDialog::new()
+            .set_file_name(file_name)
+            .add_filter(extension.to_uppercase(), &[extension]);
+        let Some(file) = dialog.save_file().await else { return };
+        if let Err(error) = file.write(contents.as_bytes()).await {
+            rfd::AsyncMessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Export failed")
+                .set_description(format!("Couldn't write {}: {}", file.file_name(), error))
+                .show()
+                .await;
+        }
+    });
+}
+
+/// Save `contents` through the browser as a download named after the note.
+#[cfg(target_arch = "wasm32")]
+fn download_file(title: &str, extension: &str, mime: &str, contents: &str) {
+    let file_name = export_file_name(title, extension);
+    // JSON string literals are valid JavaScript
+    let js = format!(
+        r#"
+        const blob = new Blob([{contents}], {{ type: {mime} }});
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(blob);
+        link.download = {file_name};
+        link.click();
+        setTimeout(() => URL.revokeObjectURL(link.href), 0);
+        "#,
+        contents = serde_json::to_string(contents).unwrap_or_default(),
+        mime = serde_json::to_string(mime).unwrap_or_default(),
+        file_name = serde_json::to_string(&file_name).unwrap_or_default(),
+    );
+    eval(&js);
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,45 @@
+use crate::state::{Backlink, BacklinkKind};
+use dioxus::prelude::*;
+
+#[component]
+pub fn Backlinks(backlinks: Vec<Backlink>, on_open: EventHandler<(String, usize)>) -> Element {
+    let (linked, unlinked): (Vec<Backlink>, Vec<Backlink>) = backlinks
+        .into_iter()
+        .partition(|b| b.kind == BacklinkKind::Linked);
+
+    rsx! {
+        div { class: "backlinks",
+            div { class: "backlinks-header",
+                "Linked mentions"
+                span { class: "backlinks-count", "{linked.len()}" }
+            }
+            for backlink in linked {
+                BacklinkItem { backlink, on_open }
+            }
+            if !unlinked.is_empty() {
+                div { class: "backlinks-header",
+                    "Unlinked mentions"
+                    span { class: "backlinks-count", "{unlinked.len()}" }
+                }
+                for backlink in unlinked {
+                    BacklinkItem { backlink, on_open }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn BacklinkItem(backlink: Backlink, on_open: EventHandler<(String, usize)>) -> Element {
+    let id = backlink.note_id.clone();
+    let line = backlink.line;
+
+    rsx! {
+        div {
+            class: "backlink-item",
+            onclick: move |_| on_open.call((id.clone(), line)),
+            div { class: "backlink-title", "{backlink.note_title}" }
+            div { class: "backlink-context", "{backlink.context}" }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::state::commands::Command;
+use crate::state::Keymap;
+use crate::state::fuzzy::{fuzzy_match, highlight_indices};
+use dioxus::prelude::*;
+
+#[component]
+pub fn CommandPalette(
+    // Commands that can run right now, in registry order
+    commands: Vec<Command>,
+    keymap: Keymap,
+    on_run: EventHandler<Command>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut selected_index = use_signal(|| 0usize);
+
+    let q = query();
+    let mut matches: Vec<(Command, i64, Vec<usize>)> = commands
+        .iter()
+        .filter_map(|command| {
+            let matched = fuzzy_match(command.title(), &q)?;
+            Some((*command, matched.score, matched.indices))
+        })
+        .collect();
+    // Stable, so an empty query keeps registry order
+    matches.sort_by_key(|m| std::cmp::Reverse(m.1));
+
+    let match_len = matches.len();
+    let current_selected = selected_index().min(match_len.saturating_sub(1));
+    let selected_command = matches.get(current_selected).map(|(command, _, _)| *command);
+
+    rsx! {
+        div {
+            class: "quick-switcher command-palette",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "quick-switcher-dialog",
+                onclick: move |evt| evt.stop_propagation(),
+                onkeydown: move |evt: KeyboardEvent| {
+                    match evt.key() {
+                        Key::Escape => on_close.call(()),
+                        Key::ArrowDown => {
+                            evt.prevent_default();
+                            if match_len > 0 {
+                                selected_index.set((current_selected + 1) % match_len);
+                            }
+                        }
+                        Key::ArrowUp => {
+                            evt.prevent_default();
+                            if match_len > 0 {
+                                selected_index.set(
+                                    if current_selected == 0 { match_len - 1 } else { current_selected - 1 }
+                                );
+                            }
+                        }
+                        Key::Enter => {
+                            if let Some(command) = selected_command {
+                                on_run.call(command);
+                            }
+                        }
+                        _ => {}
+                    }
+                },
+
+                input {
+                    class: "quick-switcher-input",
+                    r#type: "text",
+                    placeholder: "Run a command...",
+                    autofocus: true,
+                    value: "{query}",
+                    oninput: move |evt| {
+                        query.set(evt.value());
+                        selected_index.set(0);
+                    },
+                }
+
+                div { class: "quick-switcher-results",
+                    for (idx, (command, _, indices)) in matches.iter().enumerate() {
+                        div {
+                            key: "{command:?}",
+                            class: if idx == current_selected { "quick-switcher-item selected" } else { "quick-switcher-item" },
+                            onclick: {
+                                let command = *command;
+                                move |_| on_run.call(command)
+                            },
+                            span { class: "quick-switcher-title",
+                                for (text, is_match) in highlight_indices(command.title(), indices) {
+                                    if is_match {
+                                        mark { "{text}" }
+                                    } else {
+                                        "{text}"
+                                    }
+                                }
+                            }
+                            if let Some(label) = keymap.label(*command) {
+                                kbd { class: "command-shortcut", "{label}" }
+                            }
+                        }
+                    }
+                    if match_len == 0 {
+                        div { class: "quick-switcher-empty", "No matching commands" }
+                    }
+                }
+            }
+        }
+    }
+}
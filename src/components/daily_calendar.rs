@@ -0,0 +1,165 @@
+use crate::state::daily::{daily_date, today};
+use crate::state::{DailyNotesSettings, NoteSummary};
+use chrono::{Datelike, Local, Months, NaiveDate};
+use dioxus::prelude::*;
+use std::collections::HashSet;
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+#[component]
+pub fn DailyCalendar(
+    notes: Vec<NoteSummary>,
+    settings: DailyNotesSettings,
+    #[props(default)] selected_date: Option<NaiveDate>,
+    on_open_date: EventHandler<NaiveDate>,
+    on_settings_change: EventHandler<DailyNotesSettings>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let initial = selected_date.unwrap_or_else(today);
+    let mut month = use_signal(move || initial.with_day(1).unwrap_or(initial));
+    let mut is_settings_open = use_signal(|| false);
+    // Follow the open daily note to its month, leaving browsing alone otherwise
+    let selected_month = selected_date.map(|date| date.with_day(1).unwrap_or(date));
+    use_effect(use_reactive!(|selected_month| {
+        if let Some(selected) = selected_month {
+            month.set(selected);
+        }
+    }));
+
+    let daily_dates: HashSet<NaiveDate> = notes
+        .iter()
+        .filter_map(|note| daily_date(&settings, &note.title))
+        .collect();
+    let created_dates: HashSet<NaiveDate> = notes
+        .iter()
+        .map(|note| note.created_at.with_timezone(&Local).date_naive())
+        .collect();
+
+    let first = month();
+    let next_month = first.checked_add_months(Months::new(1)).unwrap_or(first);
+    let leading_blanks = first.weekday().num_days_from_monday() as usize;
+    let days_in_month = next_month.signed_duration_since(first).num_days() as u32;
+    let mut cells: Vec<Option<NaiveDate>> = vec![None; leading_blanks];
+    cells.extend((1..=days_in_month).map(|day| first.with_day(day)));
+    let month_label = first.format("%B %Y").to_string();
+    let today = today();
+
+    let format_settings = settings.clone();
+    let template_settings = settings.clone();
+
+    rsx! {
+        div {
+            class: "calendar-overlay",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "calendar",
+                onclick: move |evt| evt.stop_propagation(),
+                onkeydown: move |evt: KeyboardEvent| {
+                    if evt.key() == Key::Escape {
+                        on_close.call(());
+                    }
+                },
+
+                div { class: "calendar-header",
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| {
+                            if let Some(prev) = month().checked_sub_months(Months::new(1)) {
+                                month.set(prev);
+                            }
+                        },
+                        title: "Previous month",
+                        "‹"
+                    }
+                    span { class: "calendar-month", "{month_label}" }
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| month.set(next_month),
+                        title: "Next month",
+                        "›"
+                    }
+                }
+
+                div { class: "calendar-grid",
+                    for weekday in WEEKDAYS {
+                        span { class: "calendar-weekday", "{weekday}" }
+                    }
+                    for cell in cells {
+                        match cell {
+                            Some(date) => rsx! {
+                                button {
+                                    class: "calendar-day",
+                                    class: if daily_dates.contains(&date) { "has-daily" } else { "" },
+                                    class: if created_dates.contains(&date) { "has-notes" } else { "" },
+                                    class: if date == today { "today" } else { "" },
+                                    class: if Some(date) == selected_date { "selected" } else { "" },
+                                    onclick: move |_| on_open_date.call(date),
+                                    "{date.day()}"
+                                }
+                            },
+                            None => rsx! { span { class: "calendar-blank" } },
+                        }
+                    }
+                }
+
+                div { class: "calendar-footer",
+                    if let Some(date) = selected_date {
+                        button {
+                            class: "btn-bar",
+                            onclick: move |_| on_open_date.call(date.pred_opt().unwrap_or(date)),
+                            title: "Previous day (Ctrl+Shift+<)",
+                            "← Prev"
+                        }
+                    }
+                    button {
+                        class: "btn-bar active",
+                        onclick: move |_| on_open_date.call(today),
+                        title: "Today's note (Ctrl+D)",
+                        "Today"
+                    }
+                    if let Some(date) = selected_date {
+                        button {
+                            class: "btn-bar",
+                            onclick: move |_| on_open_date.call(date.succ_opt().unwrap_or(date)),
+                            title: "Next day (Ctrl+Shift+>)",
+                            "Next →"
+                        }
+                    }
+                    button {
+                        class: if is_settings_open() { "btn-bar active" } else { "btn-bar" },
+                        onclick: move |_| is_settings_open.set(!is_settings_open()),
+                        title: "Daily note settings",
+                        "⚙"
+                    }
+                }
+
+                if is_settings_open() {
+                    div { class: "calendar-settings",
+                        label { class: "calendar-settings-label", "Title format" }
+                        input {
+                            class: "calendar-settings-input",
+                            r#type: "text",
+                            value: "{settings.title_format}",
+                            onchange: move |evt| {
+                                let mut updated = format_settings.clone();
+                                updated.title_format = evt.value();
+                                on_settings_change.call(updated);
+                            },
+                        }
+                        label { class: "calendar-settings-label", "Template" }
+                        textarea {
+                            class: "calendar-settings-input calendar-settings-template",
+                            value: "{settings.template}",
+                            onchange: move |evt| {
+                                let mut updated = template_settings.clone();
+                                updated.template = evt.value();
+                                on_settings_change.call(updated);
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
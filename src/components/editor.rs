@@ -1,15 +1,590 @@
-use crate::state::SaveStatus;
+use crate::components::find_bar::FindBar;
+use crate::components::hybrid_view::HybridView;
+use crate::components::keymap_settings::shortcut_from_event;
+use crate::markdown::{
+    extract_headings, fold_range, highlight, indent, insert_char, insert_newline, is_pair_char, section_at,
+    section_end, toggle_format, FoldedText, InlineFormat, NoteEdit, TextEdit,
+};
+use crate::state::commands::{Command, KeyContext};
+use crate::state::replace::{find_in_text, ReplaceMatch, ReplaceQuery};
+use crate::state::vim::VimOutput;
+use crate::state::{Keymap, Pane, SaveStatus, Vim};
 use dioxus::document::eval;
 use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
 
-const LINE_HEIGHT_PX: f64 = 24.0;
+// How long the line an outline click lands on stays highlighted
+const JUMP_FLASH: Duration = Duration::from_millis(1200);
+
+// Scrolling settles this long before the outline follows it
+const SCROLL_SETTLE: Duration = Duration::from_millis(150);
+
+/// Id of the textarea in `pane`, which the scripts below look it up by.
+fn textarea_id(pane: Pane) -> &'static str {
+    match pane {
+        Pane::Primary => "editor-textarea-primary",
+        Pane::Secondary => "editor-textarea-secondary",
+    }
+}
+
+fn hybrid_id(pane: Pane) -> &'static str {
+    match pane {
+        Pane::Primary => "hybrid-view-primary",
+        Pane::Secondary => "hybrid-view-secondary",
+    }
+}
+
+/// Scroll `pane`'s live preview to the block holding note `line`.
+fn reveal_hybrid_line(pane: Pane, line: usize) {
+    let js = format!(
+        r#"
+        setTimeout(() => {{
+            const view = document.getElementById('{id}');
+            if (!view) {{
+                return;
+            }}
+            let target = null;
+            for (const block of view.querySelectorAll('[data-line]')) {{
+                if (Number(block.dataset.line) > {line}) {{
+                    break;
+                }}
+                target = block;
+            }}
+            target?.scrollIntoView({{ block: 'center' }});
+        }}, 0);
+        "#,
+        id = hybrid_id(pane)
+    );
+    eval(&js);
+}
+
+// Keeps the highlight layers under the text they belong to. Wrapped in a
+// block so it can sit beside another `textarea` binding.
+fn sync_backdrop_js(pane: Pane) -> String {
+    format!(
+        r#"{{
+            const textarea = document.getElementById('{id}');
+            if (textarea) {{
+                textarea.parentElement.querySelectorAll('.editor-backdrop').forEach((backdrop) => {{
+                    backdrop.scrollTop = textarea.scrollTop;
+                }});
+            }}
+        }}"#,
+        id = textarea_id(pane)
+    )
+}
+
+// Keys can arrive before the last input event has re-rendered, so edits
+// start from the textarea's own text
+fn get_selection_js(pane: Pane) -> String {
+    format!(
+        r#"
+        const textarea = document.getElementById('{id}');
+        return textarea ? [textarea.value, textarea.selectionStart, textarea.selectionEnd] : ["", 0, 0];
+        "#,
+        id = textarea_id(pane)
+    )
+}
+
+/// The text and UTF-16 selection of `pane`'s textarea.
+async fn read_selection(pane: Pane) -> Option<(String, usize, usize)> {
+    eval(&get_selection_js(pane)).join::<(String, usize, usize)>().await.ok()
+}
+
+/// The main editor's selection, in UTF-16 units, and scroll offset, so a tab
+/// can reopen where it was left.
+pub async fn editor_position() -> Option<((usize, usize), f64)> {
+    let js = format!(
+        r#"
+        const textarea = document.getElementById('{id}');
+        return textarea ? [textarea.selectionStart, textarea.selectionEnd, textarea.scrollTop] : null;
+        "#,
+        id = textarea_id(Pane::Primary)
+    );
+    let (start, end, scroll_top) = eval(&js).join::<Option<(usize, usize, f64)>>().await.ok().flatten()?;
+    Some(((start, end), scroll_top))
+}
+
+/// Put back a selection and scroll offset from `editor_position` once the
+/// main textarea has rendered the note.
+pub fn restore_position(selection: (usize, usize), scroll_top: f64) {
+    let (start, end) = selection;
+    let id = textarea_id(Pane::Primary);
+    let sync = sync_backdrop_js(Pane::Primary);
+    let js = format!(
+        r#"
+        setTimeout(() => {{
+            const textarea = document.getElementById('{id}');
+            if (textarea) {{
+                textarea.focus();
+                textarea.setSelectionRange({start}, {end});
+                textarea.scrollTop = {scroll_top};
+            }}
+            {sync}
+        }}, 0);
+        "#
+    );
+    eval(&js);
+}
+
+/// Focus the main editor and put the caret at byte `offset` of `content`
+/// once the textarea has rendered it.
+pub fn place_cursor(content: &str, offset: usize) {
+    select_range(Pane::Primary, content, offset, offset);
+}
+
+/// Focus `pane`'s editor and select bytes `start..end` of `content` once the
+/// textarea has rendered it.
+fn select_range(pane: Pane, content: &str, start: usize, end: usize) {
+    let start = utf16_offset(content, start);
+    let end = utf16_offset(content, end);
+    let id = textarea_id(pane);
+    let js = format!(
+        r#"
+        setTimeout(() => {{
+            const textarea = document.getElementById('{id}');
+            if (textarea) {{
+                textarea.focus();
+                textarea.setSelectionRange({start}, {end});
+            }}
+        }}, 0);
+        "#
+    );
+    eval(&js);
+}
+
+/// Put the caret at the start of the line marked by the jump backdrop with
+/// `seq` in `pane`'s textarea and scroll it into view. The marker sits in
+/// text laid out like the textarea's, so wrapping, padding and font size are
+/// all accounted for. Returns the text and the caret's UTF-16 offset.
+async fn reveal_line(pane: Pane, seq: u32) -> Option<(String, usize)> {
+    let id = textarea_id(pane);
+    let sync = sync_backdrop_js(pane);
+    let js = format!(
+        r#"
+        return await new Promise((resolve) => {{
+            // The marker appears once the editor has re-rendered
+            const reveal = (tries) => {{
+                const textarea = document.getElementById('{id}');
+                const marker = textarea?.parentElement.querySelector('.editor-line-flash[data-jump="{seq}"]');
+                if (!textarea || !marker) {{
+                    if (tries > 0) {{
+                        requestAnimationFrame(() => reveal(tries - 1));
+                    }} else {{
+                        resolve(null);
+                    }}
+                    return;
+                }}
+                const caret = Number(marker.dataset.caret);
+                textarea.focus();
+                textarea.setSelectionRange(caret, caret);
+                textarea.scrollTop = Math.max(0, marker.offsetTop - textarea.clientHeight / 3);
+                {sync}
+                resolve([textarea.value, caret]);
+            }};
+            reveal(10);
+        }});
+        "#
+    );
+    eval(&js).join::<Option<(String, usize)>>().await.ok().flatten()
+}
+
+/// Byte range of line `line` of `content`, without its line break.
+fn line_range(content: &str, line: usize) -> Option<(usize, usize)> {
+    let mut start = 0;
+    for (i, text) in content.split('\n').enumerate() {
+        if i == line {
+            return Some((start, start + text.trim_end_matches('\r').len()));
+        }
+        start += text.len() + 1;
+    }
+    None
+}
+
+// The UTF-16 offset of the first character at the reading line, a third of
+// the way down, found in the highlight layer since it wraps like the textarea
+fn reading_position_js(pane: Pane) -> String {
+    format!(
+        r#"
+        const textarea = document.getElementById('{id}');
+        const layer = textarea?.parentElement.querySelector('.editor-highlight');
+        if (!layer) return null;
+        const y = textarea.getBoundingClientRect().top + textarea.clientHeight / 3;
+        const walker = document.createTreeWalker(layer, NodeFilter.SHOW_TEXT);
+        const range = document.createRange();
+        let offset = 0;
+        for (let node = walker.nextNode(); node; node = walker.nextNode()) {{
+            range.selectNodeContents(node);
+            if (range.getBoundingClientRect().bottom < y) {{
+                offset += node.length;
+                continue;
+            }}
+            let low = 0;
+            let high = node.length;
+            while (low < high) {{
+                const mid = (low + high) >> 1;
+                range.setStart(node, mid);
+                range.setEnd(node, mid + 1);
+                if (range.getBoundingClientRect().bottom < y) {{
+                    low = mid + 1;
+                }} else {{
+                    high = mid;
+                }}
+            }}
+            return offset + low;
+        }}
+        return offset;
+        "#,
+        id = textarea_id(pane)
+    )
+}
+
+/// Folded heading lines of every note an editor has shown, so folds come
+/// back with the note.
+type Folds = Signal<HashMap<String, Vec<usize>>>;
+
+/// Fold the innermost section holding note line `line`, or the one around
+/// it when that is folded already.
+fn fold_section(mut folds: Folds, note: &str, note_id: &str, line: usize) {
+    let headings = extract_headings(note);
+    let Some(mut index) = section_at(&headings, line) else { return };
+    let mut folds = folds.write();
+    let lines = folds.entry(note_id.to_string()).or_default();
+    while lines.contains(&headings[index].line) {
+        let level = headings[index].level;
+        match headings[..index].iter().rposition(|heading| heading.level < level) {
+            Some(parent) => index = parent,
+            None => return,
+        }
+    }
+    lines.push(headings[index].line);
+}
+
+/// Unfold the sections holding note line `line`. With `hidden_only`, a
+/// folded heading on `line` itself stays folded.
+fn unfold_sections(mut folds: Folds, note: &str, note_id: &str, line: usize, hidden_only: bool) {
+    let headings = extract_headings(note);
+    let line_count = note.split('\n').count();
+    let holds = |folded: usize| {
+        headings.iter().position(|heading| heading.line == folded).is_some_and(|i| {
+            (folded < line || (folded == line && !hidden_only)) && line < section_end(&headings, i, line_count)
+        })
+    };
+    if folds.peek().get(note_id).is_some_and(|lines| lines.iter().any(|&folded| holds(folded)))
+        && let Some(lines) = folds.write().get_mut(note_id)
+    {
+        lines.retain(|&folded| !holds(folded));
+    }
+}
+
+/// The note as the editor shows it, with the sections under the headings on
+/// `lines` hidden.
+fn fold_note(note: &str, lines: &[usize]) -> FoldedText {
+    let headings = extract_headings(note);
+    let hidden = lines
+        .iter()
+        .filter_map(|&line| headings.iter().position(|heading| heading.line == line))
+        .filter_map(|i| fold_range(note, &headings, i))
+        .collect();
+    FoldedText::new(note, hidden)
+}
+
+/// Move the folds of `note` along with `edit`, dropping the ones it reaches
+/// into.
+fn shift_folds(mut folds: Folds, note: &str, note_id: &str, edit: &NoteEdit) {
+    if folds.peek().get(note_id).is_none_or(|lines| lines.is_empty()) {
+        return;
+    }
+    let headings = extract_headings(note);
+    let line = note[..edit.start].matches('\n').count();
+    let added = edit.text.matches('\n').count() as isize;
+    let removed = note[edit.start..edit.end].matches('\n').count() as isize;
+    if let Some(lines) = folds.write().get_mut(note_id) {
+        lines.retain(|&folded| {
+            let range = headings
+                .iter()
+                .position(|heading| heading.line == folded)
+                .and_then(|i| fold_range(note, &headings, i));
+            !range.is_some_and(|(start, end)| edit.touches(start, end))
+        });
+        for folded in lines.iter_mut().filter(|folded| **folded > line) {
+            *folded = folded.saturating_add_signed(added - removed);
+        }
+    }
+}
+
+/// `note` with `matches` replaced, its folds moving with the text.
+fn replace_in_note(folds: Folds, note: &str, note_id: &str, matches: &[&ReplaceMatch]) -> String {
+    // From the last match back, so earlier offsets still hold
+    let mut text = note.to_string();
+    for m in matches.iter().rev() {
+        let edit = NoteEdit {
+            start: m.start,
+            end: m.end,
+            text: m.replacement.clone(),
+        };
+        shift_folds(folds, &text, note_id, &edit);
+        text = edit.apply(&text);
+    }
+    text
+}
+
+/// Select match `m` of `note` in `pane`, unfolding the sections that hide it.
+fn reveal_note_match(pane: Pane, mut folds: Folds, note: &str, note_id: &str, m: &ReplaceMatch) {
+    let headings = extract_headings(note);
+    let hiding: Vec<usize> = (0..headings.len())
+        .filter(|&i| fold_range(note, &headings, i).is_some_and(|(start, end)| m.end > start && m.start < end))
+        .map(|i| headings[i].line)
+        .collect();
+    if folds.peek().get(note_id).is_some_and(|lines| lines.iter().any(|line| hiding.contains(line)))
+        && let Some(lines) = folds.write().get_mut(note_id)
+    {
+        lines.retain(|line| !hiding.contains(line));
+    }
+    let lines = folds.peek().get(note_id).cloned().unwrap_or_default();
+    let folded = fold_note(note, &lines);
+    reveal_match(pane, &folded.text, folded.to_visible(m.start), folded.to_visible(m.end));
+}
+
+/// Note line of the UTF-16 `position` in the editor's `text`.
+fn note_line(folded: &FoldedText, note: &str, text: &str, position: usize) -> usize {
+    let offset = folded.to_note(byte_offset(text, position), false).min(note.len());
+    note[..offset].matches('\n').count()
+}
+
+/// Byte offset of the UTF-16 `position` the textarea reports.
+fn byte_offset(content: &str, position: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in content.char_indices() {
+        if units >= position {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    content.len()
+}
+
+// Each edit waits its turn on the textarea, and input typed while one is in
+// flight is held back and replayed after it, so a key pressed mid-edit
+// lands in the edited text instead of being overwritten by it
+fn edit_text_js(pane: Pane) -> String {
+    format!(
+        r#"
+        const textarea = document.getElementById('{id}');
+        if (!textarea) {{
+            return;
+        }}
+        if (!textarea.mimeEdits) {{
+            const edits = {{ chain: Promise.resolve(), busy: 0, replaying: false }};
+            edits.run = (step) => {{
+                edits.busy += 1;
+                edits.chain = edits.chain.then(step).catch(() => {{}}).finally(() => {{
+                    edits.busy -= 1;
+                }});
+                return edits.chain;
+            }};
+            textarea.addEventListener('beforeinput', (event) => {{
+                if (edits.busy === 0 || edits.replaying) {{
+                    return;
+                }}
+                const text = event.data ?? event.dataTransfer?.getData('text/plain') ?? null;
+                let replay;
+                switch (event.inputType) {{
+                    case 'insertText':
+                    case 'insertFromPaste':
+                    case 'insertFromDrop':
+                        replay = () => text && document.execCommand('insertText', false, text);
+                        break;
+                    case 'insertLineBreak':
+                    case 'insertParagraph':
+                        replay = () => document.execCommand('insertText', false, '\n');
+                        break;
+                    case 'deleteContentBackward':
+                        replay = () => document.execCommand('delete');
+                        break;
+                    case 'deleteContentForward':
+                        replay = () => document.execCommand('forwardDelete');
+                        break;
+                    default:
+                        return;
+                }}
+                event.preventDefault();
+                edits.run(() => {{
+                    edits.replaying = true;
+                    try {{
+                        textarea.focus();
+                        replay();
+                    }} finally {{
+                        edits.replaying = false;
+                    }}
+                }});
+            }}, true);
+            textarea.mimeEdits = edits;
+        }}
+        await textarea.mimeEdits.run(async () => {{
+            dioxus.send([textarea.value, textarea.selectionStart, textarea.selectionEnd]);
+            const edit = await dioxus.recv();
+            if (!edit) {{
+                return;
+            }}
+            const [value, start, end] = edit;
+            if (value !== textarea.value) {{
+                textarea.value = value;
+                textarea.dispatchEvent(new Event('input', {{ bubbles: true }}));
+            }}
+            textarea.focus();
+            textarea.setSelectionRange(start, end);
+        }});
+        "#,
+        id = textarea_id(pane)
+    )
+}
+
+/// UTF-16 offset of byte `offset` of `content`, as textarea selections count.
+fn utf16_offset(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].encode_utf16().count()
+}
+
+/// Replace the editor text and selection with `edit` applied to them. The
+/// new text reaches the note through the textarea's input event.
+fn edit_text(pane: Pane, edit: impl FnOnce(&str, usize, usize) -> TextEdit + 'static) {
+    spawn(async move {
+        let mut script = eval(&edit_text_js(pane));
+        let Ok((content, start, end)) = script.recv::<(String, usize, usize)>().await else {
+            return;
+        };
+        let edit = edit(&content, byte_offset(&content, start), byte_offset(&content, end));
+        let start = utf16_offset(&edit.content, edit.selection.0);
+        let end = utf16_offset(&edit.content, edit.selection.1);
+        _ = script.send((edit.content, start, end));
+    });
+}
+
+fn inline_format(command: Command) -> Option<InlineFormat> {
+    match command {
+        Command::Bold => Some(InlineFormat::Bold),
+        Command::Italic => Some(InlineFormat::Italic),
+        Command::InlineCode => Some(InlineFormat::Code),
+        Command::Strikethrough => Some(InlineFormat::Strikethrough),
+        Command::InsertLink => Some(InlineFormat::Link),
+        _ => None,
+    }
+}
+
+/// The key as the Vim layer names it, or `None` for keys it leaves to the
+/// keymap and the browser (modifier chords, lone modifiers, IME input).
+fn vim_key(evt: &KeyboardEvent) -> Option<String> {
+    let modifiers = evt.modifiers();
+    if evt.is_composing() || modifiers.meta() || modifiers.alt() {
+        return None;
+    }
+    let key = evt.key();
+    if modifiers.ctrl() {
+        return match &key {
+            Key::Character(c) if c.eq_ignore_ascii_case("r") => Some(String::from("<C-r>")),
+            Key::Character(c) if c == "[" => Some(String::from("Escape")),
+            _ => None,
+        };
+    }
+    match &key {
+        Key::Character(c) => Some(c.clone()),
+        Key::Escape
+        | Key::Enter
+        | Key::Backspace
+        | Key::Tab
+        | Key::Delete
+        | Key::ArrowLeft
+        | Key::ArrowRight
+        | Key::ArrowUp
+        | Key::ArrowDown
+        | Key::Home
+        | Key::End => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+/// Show the text, selection and save request the Vim layer produced.
+fn apply_vim(
+    pane: Pane,
+    output: VimOutput,
+    content: &str,
+    on_change: EventHandler<String>,
+    on_save: EventHandler<()>,
+) {
+    let (start, end) = output.selection;
+    match output.content {
+        Some(text) => {
+            select_range(pane, &text, start, end);
+            on_change.call(text);
+        }
+        None => select_range(pane, content, start, end),
+    }
+    if output.save {
+        on_save.call(());
+    }
+}
+
+/// Select bytes `start..end` of `content` without taking focus from the find
+/// bar, and scroll the highlighted match into view. Waits for the textarea
+/// to show `content`, as it does after unfolding the match.
+fn reveal_match(pane: Pane, content: &str, start: usize, end: usize) {
+    let end = content[..end].encode_utf16().count();
+    let start = content[..start].encode_utf16().count();
+    let length = content.encode_utf16().count();
+    let id = textarea_id(pane);
+    let sync = sync_backdrop_js(pane);
+    let js = format!(
+        r#"
+        const reveal = (tries) => {{
+            const textarea = document.getElementById('{id}');
+            if (!textarea) {{
+                return;
+            }}
+            if (textarea.value.length !== {length} && tries > 0) {{
+                requestAnimationFrame(() => reveal(tries - 1));
+                return;
+            }}
+            const current = textarea.parentElement.querySelector('.editor-find-current');
+            textarea.setSelectionRange({start}, {end});
+            if (current) {{
+                textarea.scrollTop = Math.max(0, current.offsetTop - textarea.clientHeight / 3);
+            }}
+            {sync}
+        }};
+        setTimeout(() => reveal(10), 0);
+        "#
+    );
+    eval(&js);
+}
 
 #[component]
 pub fn Editor(
     content: String,
     save_status: SaveStatus,
     on_change: EventHandler<String>,
-    #[props(default)] jump_to_line: Option<usize>,
+    // Outline and search jumps; the editor in the named pane takes the request
+    #[props(default)] jump_to_line: Option<Signal<Option<(Pane, usize)>>>,
+    #[props(default)] title: String,
+    #[props(default)] has_custom_title: bool,
+    #[props(default)] on_title_change: EventHandler<Option<String>>,
+    // Folder the note is filed in, and the folders to suggest
+    #[props(default)] folder: String,
+    #[props(default)] folders: Vec<String>,
+    #[props(default)] on_folder_change: EventHandler<Option<String>>,
+    #[props(default)] keymap: Keymap,
+    // Render the note in place instead of showing the textarea
+    #[props(default)] is_hybrid: bool,
+    // Vim emulation state, when Vim keybindings are on
+    #[props(default)] vim: Option<Signal<Vim>>,
+    // Save right away, for `:w`
+    #[props(default)] on_save: EventHandler<()>,
+    // Which split pane this editor fills
+    #[props(default)] pane: Pane,
+    // Id of the note shown, which folds are kept by
+    #[props(default)] note_id: String,
+    // Note line of the caret, or of the text being read after scrolling
+    #[props(default)] on_cursor_line: EventHandler<usize>,
 ) -> Element {
     let status_text = match save_status {
         SaveStatus::Saved => "Saved",
@@ -25,50 +600,403 @@ pub fn Editor(
         SaveStatus::Error => "status-error",
     };
 
-    use_effect(move || {
-        if let Some(line) = jump_to_line {
-            let scroll_pos = (line as f64) * LINE_HEIGHT_PX;
-            let js = format!(
-                r#"
-                const textarea = document.querySelector('.editor-textarea');
-                if (textarea) {{
-                    textarea.scrollTop = {scroll_pos};
-                }}
-                "#
-            );
-            eval(&js);
+    // The textarea shows the note without its folded sections, so from here
+    // on `content` is that text and `on_change` maps edits back to the note
+    let folds: Folds = use_signal(HashMap::new);
+    let note = content;
+    let folded = if is_hybrid {
+        FoldedText::new(&note, Vec::new())
+    } else {
+        fold_note(&note, folds.read().get(&note_id).map_or(&[], Vec::as_slice))
+    };
+    let content = folded.text.clone();
+
+    // Text around each folded section, for the layer that marks them
+    let mut fold_segments = Vec::new();
+    let mut pos = 0;
+    for marker in folded.markers() {
+        fold_segments.push(content[pos..marker].to_string());
+        pos = marker;
+    }
+    let fold_tail = format!("{} ", &content[pos..]);
+
+    let on_note_change = on_change;
+    let on_change = use_callback({
+        let (folded, note, note_id) = (folded.clone(), note.clone(), note_id.clone());
+        move |text: String| {
+            let edit = folded.edit_to(&text);
+            shift_folds(folds, &note, &note_id, &edit);
+            on_note_change.call(edit.apply(&note));
         }
     });
 
+    let mut is_find_open = use_signal(|| false);
+    let mut find_query = use_signal(ReplaceQuery::default);
+    let mut current_match = use_signal(|| 0usize);
+
+    // Recomputed on every render, so matches follow edits. Folded sections
+    // are searched too and open when their match is shown
+    let find_results = if is_find_open() {
+        find_in_text(&note, &find_query())
+    } else {
+        Ok(Vec::new())
+    };
+    let find_error = find_results.as_ref().err().cloned();
+    let matches = find_results.unwrap_or_default();
+    let current = (!matches.is_empty()).then(|| current_match().min(matches.len() - 1));
+
+    // Text for the highlight layer behind the textarea: (text, Some(is_current) for matches)
+    let mut highlights: Vec<(String, Option<bool>)> = Vec::new();
+    if is_find_open() {
+        let mut pos = 0;
+        for (i, m) in matches.iter().enumerate() {
+            let (start, end) = (folded.to_visible(m.start), folded.to_visible(m.end));
+            if start == end && m.start != m.end {
+                // Out of sight in a folded section
+                continue;
+            }
+            highlights.push((content[pos..start].to_string(), None));
+            highlights.push((content[start..end].to_string(), Some(current == Some(i))));
+            pos = end;
+        }
+        // The trailing space keeps a final empty line as tall as in the textarea
+        highlights.push((format!("{} ", &content[pos..]), None));
+    }
+
+    // Normal and visual mode show a block cursor by selecting a character
+    let is_vim_block = vim.is_some_and(|vim| !vim.read().is_insert());
+
+    // Styled copy of the text drawn under the transparent textarea text
+    let syntax = if is_hybrid { Vec::new() } else { highlight(&content) };
+
+    let mut scroll_seq = use_signal(|| 0u32);
+    let scroll_folded = folded.clone();
+    let scroll_note = note.clone();
+    let click_folded = folded.clone();
+    let click_note = note.clone();
+    let key_folded = folded.clone();
+    let key_note = note.clone();
+    let fold_folded = folded.clone();
+    let fold_note = note.clone();
+    let fold_note_id = note_id.clone();
+
+    let next_matches = matches.clone();
+    let next_note = (note.clone(), note_id.clone());
+    let previous_matches = matches.clone();
+    let previous_note = (note.clone(), note_id.clone());
+    let replace_matches = matches.clone();
+    let replace_note = (note.clone(), note_id.clone());
+    let replace_all_matches = matches.clone();
+    let replace_all_note = (note.clone(), note_id.clone());
+
+    // Line briefly highlighted after a jump, tagged so the reveal script
+    // finds this jump's marker rather than a fading earlier one
+    let mut jump_flash = use_signal(|| None::<(usize, u32)>);
+    let flash_range = jump_flash().and_then(|(line, seq)| {
+        let (start, end) = line_range(&note, line)?;
+        let (start, end) = (folded.to_visible(start), folded.to_visible(end));
+        Some(((start, end), content[..start].encode_utf16().count(), seq))
+    });
+
+    // Requests are taken, so jumping to the same line again fires anew
+    use_effect({
+        let note = note.clone();
+        use_reactive!(|(vim, is_hybrid, note, note_id)| {
+            let Some(mut jump_to_line) = jump_to_line else { return };
+            let Some((target, line)) = jump_to_line() else { return };
+            if target != pane {
+                return;
+            }
+            jump_to_line.set(None);
+            if is_hybrid {
+                reveal_hybrid_line(pane, line);
+                return;
+            }
+            unfold_sections(folds, &note, &note_id, line, true);
+            let seq = jump_flash.peek().map_or(0, |(_, seq)| seq.wrapping_add(1));
+            jump_flash.set(Some((line, seq)));
+            spawn(async move {
+                if let Some((text, caret)) = reveal_line(pane, seq).await
+                    && let Some(mut vim) = vim.filter(|vim| !vim.peek().is_insert())
+                {
+                    let caret = byte_offset(&text, caret);
+                    vim.write().set_selection(&text, caret, caret);
+                    let (start, end) = vim.peek().selection(&text);
+                    select_range(pane, &text, start, end);
+                }
+                async_std::task::sleep(JUMP_FLASH).await;
+                if jump_flash.peek().is_some_and(|(_, current)| current == seq) {
+                    jump_flash.set(None);
+                }
+            });
+        })
+    });
+
     rsx! {
         div { class: "editor",
-            textarea {
-                class: "editor-textarea",
-                placeholder: "Start writing...",
-                value: "{content}",
-                oninput: move |evt| on_change.call(evt.value()),
-                onkeydown: move |evt: KeyboardEvent| {
-                    if evt.modifiers().ctrl() || evt.modifiers().meta() {
-                        match evt.key() {
-                            Key::Character(c) if c == "b" || c == "B" => {
-                                evt.prevent_default();
-                                let new_content = format!("{}****", content);
-                                on_change.call(new_content);
+            div { class: "editor-title",
+                input {
+                    class: "editor-title-input",
+                    r#type: "text",
+                    placeholder: "Title",
+                    title: "Note title (defaults to the first line)",
+                    value: "{title}",
+                    onchange: move |evt| on_title_change.call(Some(evt.value())),
+                }
+                if has_custom_title {
+                    button {
+                        class: "editor-title-reset",
+                        onclick: move |_| on_title_change.call(None),
+                        title: "Use the first line as title",
+                        "↺"
+                    }
+                }
+                input {
+                    class: "editor-folder-input",
+                    r#type: "text",
+                    placeholder: "Folder",
+                    title: "Folder, used by in: searches and the graph filter",
+                    list: "{textarea_id(pane)}-folders",
+                    value: "{folder}",
+                    onchange: move |evt| on_folder_change.call(Some(evt.value())),
+                }
+                datalist { id: "{textarea_id(pane)}-folders",
+                    for name in folders {
+                        option { value: "{name}" }
+                    }
+                }
+            }
+            if is_find_open() && !is_hybrid {
+                FindBar {
+                    query: find_query(),
+                    match_count: matches.len(),
+                    current,
+                    error: find_error,
+                    on_query_change: move |query: ReplaceQuery| {
+                        find_query.set(query);
+                        current_match.set(0);
+                    },
+                    on_next: move |_| {
+                        if !next_matches.is_empty() {
+                            let next = current.map_or(0, |i| (i + 1) % next_matches.len());
+                            current_match.set(next);
+                            let (note, note_id) = &next_note;
+                            reveal_note_match(pane, folds, note, note_id, &next_matches[next]);
+                        }
+                    },
+                    on_previous: move |_| {
+                        if !previous_matches.is_empty() {
+                            let len = previous_matches.len();
+                            let previous = current.map_or(len - 1, |i| (i + len - 1) % len);
+                            current_match.set(previous);
+                            let (note, note_id) = &previous_note;
+                            reveal_note_match(pane, folds, note, note_id, &previous_matches[previous]);
+                        }
+                    },
+                    on_replace: move |_| {
+                        // The following match moves into this index
+                        if let Some(m) = current.and_then(|i| replace_matches.get(i)) {
+                            let (note, note_id) = &replace_note;
+                            on_note_change.call(replace_in_note(folds, note, note_id, &[m]));
+                        }
+                    },
+                    on_replace_all: move |_| {
+                        if !replace_all_matches.is_empty() {
+                            let (note, note_id) = &replace_all_note;
+                            let matches: Vec<&ReplaceMatch> = replace_all_matches.iter().collect();
+                            on_note_change.call(replace_in_note(folds, note, note_id, &matches));
+                        }
+                    },
+                    on_close: move |_| {
+                        is_find_open.set(false);
+                        eval(&format!("document.getElementById('{}')?.focus();", textarea_id(pane)));
+                    },
+                }
+            }
+            if is_hybrid {
+                HybridView { id: hybrid_id(pane), content: note.clone(), on_change: on_note_change }
+            } else {
+                div { class: "editor-surface",
+                    if is_find_open() {
+                        div { class: "editor-backdrop", aria_hidden: "true",
+                            for (text, is_current) in highlights {
+                                match is_current {
+                                    None => rsx! { "{text}" },
+                                    Some(true) => rsx! { mark { class: "editor-find-current", "{text}" } },
+                                    Some(false) => rsx! { mark { "{text}" } },
+                                }
                             }
-                            Key::Character(c) if c == "i" || c == "I" => {
-                                evt.prevent_default();
-                                let new_content = format!("{}**", content);
-                                on_change.call(new_content);
+                        }
+                    }
+                    if !fold_segments.is_empty() {
+                        div { class: "editor-backdrop", aria_hidden: "true",
+                            for segment in fold_segments {
+                                "{segment}"
+                                span { class: "editor-fold-marker", title: "Folded section" }
                             }
-                            Key::Character(c) if c == "k" || c == "K" => {
-                                evt.prevent_default();
-                                let new_content = format!("{}[](url)", content);
-                                on_change.call(new_content);
+                            "{fold_tail}"
+                        }
+                    }
+                    if let Some(((start, end), caret, seq)) = flash_range {
+                        div { class: "editor-backdrop", aria_hidden: "true",
+                            "{&content[..start]}"
+                            mark { class: "editor-line-flash", "data-jump": "{seq}", "data-caret": "{caret}",
+                                // An empty line still needs a box to scroll to
+                                if start == end { " " } else { "{&content[start..end]}" }
                             }
-                            _ => {}
+                            "{&content[end..]} "
+                        }
+                    }
+                    div { class: "editor-backdrop editor-highlight", aria_hidden: "true",
+                        for span in syntax {
+                            span { class: "{span.class}", "{span.text}" }
                         }
+                        // Keeps a final empty line as tall as in the textarea
+                        " "
+                    }
+                    textarea {
+                        id: textarea_id(pane),
+                        class: "editor-textarea",
+                        class: if is_vim_block { "editor-vim-block" } else { "" },
+                        placeholder: "Start writing...",
+                        value: "{content}",
+                        oninput: move |evt| on_change.call(evt.value()),
+                        onscroll: move |_| {
+                            eval(&sync_backdrop_js(pane));
+                            // Let the outline follow the text being read
+                            let seq = scroll_seq().wrapping_add(1);
+                            scroll_seq.set(seq);
+                            let (folded, note) = (scroll_folded.clone(), scroll_note.clone());
+                            spawn(async move {
+                                async_std::task::sleep(SCROLL_SETTLE).await;
+                                if scroll_seq() != seq {
+                                    return;
+                                }
+                                let position = eval(&reading_position_js(pane)).join::<Option<usize>>().await;
+                                if let Some(position) = position.ok().flatten() {
+                                    on_cursor_line.call(note_line(&folded, &note, &folded.text, position));
+                                }
+                            });
+                        },
+                        onmouseup: move |_| {
+                            // Clicks move the Vim cursor; drags start visual mode
+                            let vim = vim.filter(|vim| !vim.peek().is_insert());
+                            let (folded, note) = (click_folded.clone(), click_note.clone());
+                            spawn(async move {
+                                let Some((text, start, end)) = read_selection(pane).await else {
+                                    return;
+                                };
+                                on_cursor_line.call(note_line(&folded, &note, &text, end));
+                                let Some(mut vim) = vim else { return };
+                                vim.write().set_selection(&text, byte_offset(&text, start), byte_offset(&text, end));
+                                let (start, end) = vim.peek().selection(&text);
+                                select_range(pane, &text, start, end);
+                            });
+                        },
+                        onkeyup: move |_| {
+                            let (folded, note) = (key_folded.clone(), key_note.clone());
+                            spawn(async move {
+                                if let Some((text, _, end)) = read_selection(pane).await {
+                                    on_cursor_line.call(note_line(&folded, &note, &text, end));
+                                }
+                            });
+                        },
+                        onkeydown: move |evt: KeyboardEvent| {
+                            if let (Some(mut vim), Some(key)) = (vim, vim_key(&evt)) {
+                                if !vim.peek().is_insert() {
+                                    evt.prevent_default();
+                                    evt.stop_propagation();
+                                    // Queued like other edits, so a key repeated before the
+                                    // next render acts on the text the last one left
+                                    edit_text(pane, move |content, start, end| {
+                                        let Some(output) = vim.write().handle_key(&key, content) else {
+                                            return TextEdit {
+                                                content: content.to_string(),
+                                                selection: (start, end),
+                                            };
+                                        };
+                                        // `:w` leaves the text as it is
+                                        if output.save {
+                                            on_save.call(());
+                                        }
+                                        TextEdit {
+                                            content: output.content.unwrap_or_else(|| content.to_string()),
+                                            selection: output.selection,
+                                        }
+                                    });
+                                    return;
+                                }
+                                if key == "Escape" {
+                                    evt.prevent_default();
+                                    evt.stop_propagation();
+                                    spawn(async move {
+                                        let Some((text, caret, _)) = read_selection(pane).await else {
+                                            return;
+                                        };
+                                        let output = vim.write().exit_insert(&text, byte_offset(&text, caret));
+                                        apply_vim(pane, output, &text, on_change, on_save);
+                                    });
+                                    return;
+                                }
+                            }
+                            let Some(command) = keymap.lookup(&shortcut_from_event(&evt), KeyContext::Editor) else {
+                                let modifiers = evt.modifiers();
+                                if modifiers.ctrl() || modifiers.meta() || modifiers.alt() || evt.is_composing() {
+                                    return;
+                                }
+                                // Markdown-aware typing for lists, quotes and pairs
+                                match evt.key() {
+                                    Key::Enter if !modifiers.shift() => {
+                                        evt.prevent_default();
+                                        edit_text(pane, insert_newline);
+                                    }
+                                    Key::Tab => {
+                                        evt.prevent_default();
+                                        let outdent = modifiers.shift();
+                                        edit_text(pane, move |content, start, end| indent(content, start, end, outdent));
+                                    }
+                                    Key::Character(c) => {
+                                        let mut chars = c.chars();
+                                        if let (Some(c), None) = (chars.next(), chars.next())
+                                            && is_pair_char(c)
+                                        {
+                                            evt.prevent_default();
+                                            edit_text(pane, move |content, start, end| insert_char(content, start, end, c));
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                                return;
+                            };
+                            // Editor bindings shadow global ones with the same chord
+                            evt.prevent_default();
+                            evt.stop_propagation();
+                            if command == Command::FindInNote {
+                                is_find_open.set(true);
+                                let js = format!(
+                                    "setTimeout(() => {{ const input = document.getElementById('{}')?.closest('.editor')?.querySelector('.find-bar-find'); if (input) {{ input.focus(); input.select(); }} }}, 0);",
+                                    textarea_id(pane)
+                                );
+                                eval(&js);
+                            } else if matches!(command, Command::FoldSection | Command::UnfoldSection) {
+                                let (folded, note, note_id) = (fold_folded.clone(), fold_note.clone(), fold_note_id.clone());
+                                spawn(async move {
+                                    let Some((text, caret, _)) = read_selection(pane).await else {
+                                        return;
+                                    };
+                                    let line = note_line(&folded, &note, &text, caret);
+                                    if command == Command::FoldSection {
+                                        fold_section(folds, &note, &note_id, line);
+                                    } else {
+                                        unfold_sections(folds, &note, &note_id, line, false);
+                                    }
+                                });
+                            } else if let Some(format) = inline_format(command) {
+                                edit_text(pane, move |content, start, end| toggle_format(content, start, end, format));
+                            }
+                        },
                     }
-                },
+                }
             }
             div { class: "editor-status {status_class}",
                 "{status_text}"
@@ -0,0 +1,146 @@
+use crate::state::replace::ReplaceQuery;
+use dioxus::prelude::*;
+
+#[component]
+pub fn FindBar(
+    query: ReplaceQuery,
+    match_count: usize,
+    // Index of the highlighted match
+    current: Option<usize>,
+    #[props(default)] error: Option<String>,
+    on_query_change: EventHandler<ReplaceQuery>,
+    on_next: EventHandler<()>,
+    on_previous: EventHandler<()>,
+    on_replace: EventHandler<()>,
+    on_replace_all: EventHandler<()>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let count_text = match (current, match_count) {
+        (_, 0) if !query.find.is_empty() => String::from("No results"),
+        (_, 0) => String::new(),
+        (Some(index), count) => format!("{} of {}", index + 1, count),
+        (None, count) => format!("{} found", count),
+    };
+
+    let find_query = query.clone();
+    let replace_query = query.clone();
+    let case_query = query.clone();
+    let regex_query = query.clone();
+
+    rsx! {
+        div {
+            class: "find-bar",
+            onkeydown: move |evt: KeyboardEvent| {
+                // Keep find bar keys away from the editor and app shortcuts
+                evt.stop_propagation();
+                if evt.key() == Key::Escape {
+                    evt.prevent_default();
+                    on_close.call(());
+                }
+            },
+
+            div { class: "find-bar-row",
+                input {
+                    class: "find-bar-input find-bar-find",
+                    r#type: "text",
+                    placeholder: "Find",
+                    value: "{query.find}",
+                    oninput: move |evt| {
+                        let mut updated = find_query.clone();
+                        updated.find = evt.value();
+                        on_query_change.call(updated);
+                    },
+                    onkeydown: move |evt: KeyboardEvent| {
+                        if evt.key() == Key::Enter {
+                            evt.prevent_default();
+                            if evt.modifiers().shift() {
+                                on_previous.call(());
+                            } else {
+                                on_next.call(());
+                            }
+                        }
+                    },
+                }
+                span {
+                    class: "find-bar-count",
+                    class: if error.is_some() || (match_count == 0 && !query.find.is_empty()) { "no-results" } else { "" },
+                    title: error.clone().unwrap_or_default(),
+                    if error.is_some() { "Invalid pattern" } else { "{count_text}" }
+                }
+                button {
+                    class: if query.case_sensitive { "btn-bar active" } else { "btn-bar" },
+                    onclick: move |_| {
+                        let mut updated = case_query.clone();
+                        updated.case_sensitive = !updated.case_sensitive;
+                        on_query_change.call(updated);
+                    },
+                    title: "Match case",
+                    "Aa"
+                }
+                button {
+                    class: if query.regex { "btn-bar active" } else { "btn-bar" },
+                    onclick: move |_| {
+                        let mut updated = regex_query.clone();
+                        updated.regex = !updated.regex;
+                        on_query_change.call(updated);
+                    },
+                    title: "Regular expression",
+                    ".*"
+                }
+                button {
+                    class: "btn-bar",
+                    disabled: match_count == 0,
+                    onclick: move |_| on_previous.call(()),
+                    title: "Previous match (Shift+Enter)",
+                    "↑"
+                }
+                button {
+                    class: "btn-bar",
+                    disabled: match_count == 0,
+                    onclick: move |_| on_next.call(()),
+                    title: "Next match (Enter)",
+                    "↓"
+                }
+                button {
+                    class: "btn-bar",
+                    onclick: move |_| on_close.call(()),
+                    title: "Close (Escape)",
+                    "×"
+                }
+            }
+            div { class: "find-bar-row",
+                input {
+                    class: "find-bar-input",
+                    r#type: "text",
+                    placeholder: if query.regex { "Replace ($1 for groups)" } else { "Replace" },
+                    value: "{query.replace}",
+                    oninput: move |evt| {
+                        let mut updated = replace_query.clone();
+                        updated.replace = evt.value();
+                        on_query_change.call(updated);
+                    },
+                    onkeydown: move |evt: KeyboardEvent| {
+                        if evt.key() == Key::Enter {
+                            evt.prevent_default();
+                            on_replace.call(());
+                        }
+                    },
+                }
+                button {
+                    class: "btn-bar",
+                    disabled: match_count == 0,
+                    onclick: move |_| on_replace.call(()),
+                    title: "Replace this match (Enter)",
+                    "Replace"
+                }
+                button {
+                    class: "btn-bar",
+                    disabled: match_count == 0,
+                    onclick: move |_| on_replace_all.call(()),
+                    title: "Replace every match",
+                    "All"
+                }
+            }
+        }
+    }
+}
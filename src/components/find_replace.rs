@@ -0,0 +1,231 @@
+use crate::state::replace::{NoteMatches, ReplaceQuery};
+use dioxus::prelude::*;
+use std::collections::HashSet;
+
+#[component]
+pub fn FindReplace(
+    query: ReplaceQuery,
+    results: Vec<NoteMatches>,
+    #[props(default)] error: Option<String>,
+    #[props(default)] can_undo: bool,
+    on_query_change: EventHandler<ReplaceQuery>,
+    // Matches to skip, by (note id, match start)
+    on_apply: EventHandler<HashSet<(String, usize)>>,
+    // Returns the titles of notes left alone because they were edited since
+    on_undo: Callback<(), Vec<String>>,
+    on_open_match: EventHandler<(String, usize)>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut excluded = use_signal(HashSet::<(String, usize)>::new);
+    let mut message = use_signal(|| None::<String>);
+
+    let total: usize = results.iter().map(|r| r.matches.len()).sum();
+    let skipped = excluded().len();
+    let included = total.saturating_sub(skipped);
+    let affected_notes = results
+        .iter()
+        .filter(|r| r.matches.iter().any(|m| !excluded().contains(&(r.note_id.clone(), m.start))))
+        .count();
+
+    // Edits start a fresh preview with every match included
+    let mut change_query = move |updated: ReplaceQuery| {
+        excluded.set(HashSet::new());
+        message.set(None);
+        on_query_change.call(updated);
+    };
+
+    let find_query = query.clone();
+    let replace_query = query.clone();
+    let case_query = query.clone();
+    let word_query = query.clone();
+    let regex_query = query.clone();
+
+    rsx! {
+        div {
+            class: "find-replace-overlay",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "find-replace",
+                onclick: move |evt| evt.stop_propagation(),
+                onkeydown: move |evt: KeyboardEvent| {
+                    if evt.key() == Key::Escape {
+                        on_close.call(());
+                    }
+                },
+
+                div { class: "find-replace-fields",
+                    div { class: "find-replace-row",
+                        input {
+                            class: "find-replace-input",
+                            r#type: "text",
+                            placeholder: "Find in all notes",
+                            autofocus: true,
+                            value: "{query.find}",
+                            oninput: move |evt| {
+                                let mut updated = find_query.clone();
+                                updated.find = evt.value();
+                                change_query(updated);
+                            },
+                        }
+                        button {
+                            class: if query.case_sensitive { "btn-bar active" } else { "btn-bar" },
+                            onclick: move |_| {
+                                let mut updated = case_query.clone();
+                                updated.case_sensitive = !updated.case_sensitive;
+                                change_query(updated);
+                            },
+                            title: "Match case",
+                            "Aa"
+                        }
+                        button {
+                            class: if query.whole_word { "btn-bar active" } else { "btn-bar" },
+                            onclick: move |_| {
+                                let mut updated = word_query.clone();
+                                updated.whole_word = !updated.whole_word;
+                                change_query(updated);
+                            },
+                            title: "Whole word",
+                            "W"
+                        }
+                        button {
+                            class: if query.regex { "btn-bar active" } else { "btn-bar" },
+                            onclick: move |_| {
+                                let mut updated = regex_query.clone();
+                                updated.regex = !updated.regex;
+                                change_query(updated);
+                            },
+                            title: "Regular expression",
+                            ".*"
+                        }
+                    }
+                    div { class: "find-replace-row",
+                        input {
+                            class: "find-replace-input",
+                            r#type: "text",
+                            placeholder: if query.regex { "Replace with ($1 for groups)" } else { "Replace with" },
+                            value: "{query.replace}",
+                            oninput: move |evt| {
+                                let mut updated = replace_query.clone();
+                                updated.replace = evt.value();
+                                change_query(updated);
+                            },
+                        }
+                        button {
+                            class: "btn-bar active",
+                            disabled: included == 0,
+                            onclick: move |_| {
+                                on_apply.call(excluded());
+                                message.set(Some(format!(
+                                    "Replaced {} {} in {} {}",
+                                    included,
+                                    if included == 1 { "match" } else { "matches" },
+                                    affected_notes,
+                                    if affected_notes == 1 { "note" } else { "notes" },
+                                )));
+                                excluded.set(HashSet::new());
+                            },
+                            title: "Replace every included match and save",
+                            "Replace all"
+                        }
+                    }
+                }
+
+                if let Some(error) = error.clone() {
+                    div { class: "search-error", "{error}" }
+                }
+
+                div { class: "find-replace-summary",
+                    if let Some(text) = message() {
+                        span { "{text}" }
+                    } else if !query.find.is_empty() && error.is_none() {
+                        span { "{included} of {total} matches in {results.len()} notes" }
+                    }
+                    if can_undo {
+                        button {
+                            class: "btn-bar",
+                            onclick: move |_| {
+                                let edited = on_undo.call(());
+                                message.set(Some(if edited.is_empty() {
+                                    String::from("Replacement undone")
+                                } else {
+                                    format!("Replacement undone, except in notes edited since: {}", edited.join(", "))
+                                }));
+                            },
+                            title: "Restore the notes changed by the last replace",
+                            "Undo replace"
+                        }
+                    }
+                }
+
+                div { class: "find-replace-results",
+                    for result in results.iter() {
+                        div {
+                            key: "{result.note_id}",
+                            class: "find-replace-note",
+                            div { class: "find-replace-note-header",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: result.matches.iter().any(|m| !excluded().contains(&(result.note_id.clone(), m.start))),
+                                    onchange: {
+                                        let keys: Vec<(String, usize)> = result
+                                            .matches
+                                            .iter()
+                                            .map(|m| (result.note_id.clone(), m.start))
+                                            .collect();
+                                        move |_| {
+                                            let mut set = excluded();
+                                            if keys.iter().any(|key| !set.contains(key)) {
+                                                set.extend(keys.iter().cloned());
+                                            } else {
+                                                for key in &keys {
+                                                    set.remove(key);
+                                                }
+                                            }
+                                            excluded.set(set);
+                                        }
+                                    },
+                                }
+                                span { class: "find-replace-note-title", "{result.note_title}" }
+                                span { class: "find-replace-count", "{result.matches.len()}" }
+                            }
+                            for m in result.matches.iter() {
+                                div {
+                                    key: "{result.note_id}-{m.start}",
+                                    class: "find-replace-match",
+                                    class: if excluded().contains(&(result.note_id.clone(), m.start)) { "excluded" } else { "" },
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: !excluded().contains(&(result.note_id.clone(), m.start)),
+                                        onchange: {
+                                            let key = (result.note_id.clone(), m.start);
+                                            move |_| {
+                                                let mut set = excluded();
+                                                if !set.remove(&key) {
+                                                    set.insert(key.clone());
+                                                }
+                                                excluded.set(set);
+                                            }
+                                        },
+                                    }
+                                    span { class: "find-replace-line", "{m.line + 1}" }
+                                    span {
+                                        class: "find-replace-context",
+                                        onclick: {
+                                            let target = (result.note_id.clone(), m.line);
+                                            move |_| on_open_match.call(target.clone())
+                                        },
+                                        "{m.before}"
+                                        del { "{m.matched}" }
+                                        ins { "{m.replacement}" }
+                                        "{m.after}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::state::NoteGraph;
+use dioxus::prelude::*;
+
+const VIEW_WIDTH: f64 = 1000.0;
+const VIEW_HEIGHT: f64 = 700.0;
+const LAYOUT_ITERATIONS: usize = 120;
+
+struct NodeView {
+    id: String,
+    title: String,
+    x: f64,
+    y: f64,
+    radius: f64,
+    label_y: f64,
+    is_current: bool,
+}
+
+#[component]
+pub fn GraphView(
+    graph: ReadSignal<NoteGraph>,
+    current_id: ReadSignal<Option<String>>,
+    on_open: EventHandler<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut tag_filter = use_signal(String::new);
+    let mut folder_filter = use_signal(String::new);
+    let mut is_local = use_signal(|| false);
+    let mut zoom = use_signal(|| 1.0f64);
+    let mut pan = use_signal(|| (0.0f64, 0.0f64));
+    let mut drag_origin = use_signal(|| None::<(f64, f64)>);
+    // View box units per screen pixel, as the canvas is drawn now
+    let mut view_scale = use_signal(|| 1.0f64);
+
+    // Re-layout only when the graph or filters change, not on pan/zoom
+    let layout = use_memo(move || {
+        let graph = graph.read();
+        let mut visible = match (is_local(), current_id()) {
+            (true, Some(id)) => graph.local(&id),
+            _ => graph.clone(),
+        };
+
+        let tag = tag_filter();
+        if !tag.is_empty() {
+            visible = visible.filtered(|node| node.tags.contains(&tag));
+        }
+        let folder = folder_filter();
+        if !folder.is_empty() {
+            visible = visible.filtered(|node| node.folder.as_deref() == Some(folder.as_str()));
+        }
+
+        let positions = force_layout(visible.nodes.len(), &visible.edges);
+        (visible, positions)
+    });
+
+    let (visible, positions) = layout();
+    let tags = graph.read().tags();
+    let folders = graph.read().folders();
+    let current = current_id();
+
+    let edge_lines: Vec<(f64, f64, f64, f64)> = visible
+        .edges
+        .iter()
+        .map(|&(a, b)| (positions[a].0, positions[a].1, positions[b].0, positions[b].1))
+        .collect();
+
+    let nodes: Vec<NodeView> = visible
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let radius = 5.0 + (visible.degree(i) as f64).sqrt() * 2.5;
+            NodeView {
+                id: node.id.clone(),
+                title: node.title.clone(),
+                x: positions[i].0,
+                y: positions[i].1,
+                radius,
+                label_y: positions[i].1 + radius + 14.0,
+                is_current: current.as_deref() == Some(node.id.as_str()),
+            }
+        })
+        .collect();
+
+    let node_count = visible.nodes.len();
+    let edge_count = visible.edges.len();
+    let (pan_x, pan_y) = pan();
+    let scale = zoom();
+
+    rsx! {
+        div {
+            class: "graph-view",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "graph-dialog",
+                // Focused so Escape reaches it
+                tabindex: "-1",
+                onmounted: move |evt| async move {
+                    _ = evt.set_focus(true).await;
+                },
+                onclick: move |evt| evt.stop_propagation(),
+                onkeydown: move |evt: KeyboardEvent| {
+                    if evt.key() == Key::Escape {
+                        on_close.call(());
+                    }
+                },
+
+                div { class: "graph-toolbar",
+                    select {
+                        class: "graph-filter",
+                        value: "{tag_filter}",
+                        onchange: move |evt| tag_filter.set(evt.value()),
+                        option { value: "", "All tags" }
+                        for tag in tags {
+                            option { value: "{tag}", "#{tag}" }
+                        }
+                    }
+                    if !folders.is_empty() {
+                        select {
+                            class: "graph-filter",
+                            value: "{folder_filter}",
+                            onchange: move |evt| folder_filter.set(evt.value()),
+                            option { value: "", "All folders" }
+                            for folder in folders {
+                                option { value: "{folder}", "{folder}" }
+                            }
+                        }
+                    }
+                    button {
+                        class: if is_local() { "btn-bar active" } else { "btn-bar" },
+                        onclick: move |_| is_local.set(!is_local()),
+                        title: "Only show notes linked to the current note",
+                        "Local graph"
+                    }
+                    span { class: "graph-count", "{node_count} notes · {edge_count} links" }
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| {
+                            zoom.set(1.0);
+                            pan.set((0.0, 0.0));
+                        },
+                        title: "Reset view",
+                        "⟲"
+                    }
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| on_close.call(()),
+                        title: "Close (Esc)",
+                        "×"
+                    }
+                }
+
+                svg {
+                    class: "graph-canvas",
+                    view_box: "0 0 {VIEW_WIDTH} {VIEW_HEIGHT}",
+                    onwheel: move |evt| {
+                        evt.prevent_default();
+                        let factor = if evt.delta().strip_units().y > 0.0 { 0.9 } else { 1.1 };
+                        zoom.set((zoom() * factor).clamp(0.2, 5.0));
+                    },
+                    // The view box is fitted inside the canvas, keeping its shape
+                    onresize: move |evt: ResizeEvent| {
+                        if let Ok(size) = evt.get_content_box_size()
+                            && size.width > 0.0
+                            && size.height > 0.0
+                        {
+                            view_scale.set((VIEW_WIDTH / size.width).max(VIEW_HEIGHT / size.height));
+                        }
+                    },
+                    onmousedown: move |evt| {
+                        let point = evt.client_coordinates() * view_scale();
+                        let (x, y) = pan();
+                        drag_origin.set(Some((point.x - x, point.y - y)));
+                    },
+                    onmousemove: move |evt| {
+                        if let Some((origin_x, origin_y)) = drag_origin() {
+                            let point = evt.client_coordinates() * view_scale();
+                            pan.set((point.x - origin_x, point.y - origin_y));
+                        }
+                    },
+                    onmouseup: move |_| drag_origin.set(None),
+                    onmouseleave: move |_| drag_origin.set(None),
+
+                    g { transform: "translate({pan_x} {pan_y}) scale({scale})",
+                        for (x1, y1, x2, y2) in edge_lines {
+                            line { class: "graph-edge", x1: "{x1}", y1: "{y1}", x2: "{x2}", y2: "{y2}" }
+                        }
+                        for node in nodes {
+                            g {
+                                key: "{node.id}",
+                                class: if node.is_current { "graph-node current" } else { "graph-node" },
+                                onmousedown: move |evt| evt.stop_propagation(),
+                                onclick: {
+                                    let id = node.id.clone();
+                                    move |_| on_open.call(id.clone())
+                                },
+                                circle { cx: "{node.x}", cy: "{node.y}", r: "{node.radius}" }
+                                text {
+                                    x: "{node.x}",
+                                    y: "{node.label_y}",
+                                    text_anchor: "middle",
+                                    "{node.title}"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if node_count == 0 {
+                    div { class: "graph-empty", "No notes match these filters" }
+                }
+            }
+        }
+    }
+}
+
+/// Fruchterman-Reingold layout inside the view box. Nodes start on a
+/// sunflower spiral so the result is deterministic between renders.
+fn force_layout(node_count: usize, edges: &[(usize, usize)]) -> Vec<(f64, f64)> {
+    let center = (VIEW_WIDTH / 2.0, VIEW_HEIGHT / 2.0);
+    if node_count == 0 {
+        return Vec::new();
+    }
+
+    let k = (VIEW_WIDTH * VIEW_HEIGHT / node_count as f64).sqrt() * 0.5;
+    let mut positions: Vec<(f64, f64)> = (0..node_count)
+        .map(|i| {
+            let radius = k * 0.5 * (i as f64).sqrt();
+            let angle = i as f64 * 2.399_963;
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect();
+
+    let mut temperature = VIEW_WIDTH / 10.0;
+    for _ in 0..LAYOUT_ITERATIONS {
+        let mut displacement = vec![(0.0f64, 0.0f64); node_count];
+
+        // Every pair repels
+        for i in 0..node_count {
+            for j in (i + 1)..node_count {
+                let dx = positions[i].0 - positions[j].0;
+                let dy = positions[i].1 - positions[j].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = k * k / distance;
+                displacement[i].0 += dx / distance * force;
+                displacement[i].1 += dy / distance * force;
+                displacement[j].0 -= dx / distance * force;
+                displacement[j].1 -= dy / distance * force;
+            }
+        }
+
+        // Linked notes attract
+        for &(a, b) in edges {
+            let dx = positions[a].0 - positions[b].0;
+            let dy = positions[a].1 - positions[b].1;
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = distance * distance / k;
+            displacement[a].0 -= dx / distance * force;
+            displacement[a].1 -= dy / distance * force;
+            displacement[b].0 += dx / distance * force;
+            displacement[b].1 += dy / distance * force;
+        }
+
+        for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+            // Gentle gravity keeps unlinked notes from drifting to the edges
+            let dx = dx + (center.0 - position.0) * 0.05;
+            let dy = dy + (center.1 - position.1) * 0.05;
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 = (position.0 + dx / length * step).clamp(20.0, VIEW_WIDTH - 20.0);
+                position.1 = (position.1 + dy / length * step).clamp(20.0, VIEW_HEIGHT - 20.0);
+            }
+        }
+
+        temperature *= 0.95;
+    }
+
+    positions
+}
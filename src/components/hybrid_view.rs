@@ -0,0 +1,193 @@
+use crate::markdown::{extract_tasks, render_markdown, split_blocks, toggle_task};
+use dioxus::document::eval;
+use dioxus::prelude::*;
+use std::ops::Range;
+
+const FOCUS_SOURCE_JS: &str = r#"
+    setTimeout(() => {
+        const textarea = document.querySelector('.hybrid-source');
+        if (textarea) {
+            textarea.focus();
+            textarea.setSelectionRange(textarea.value.length, textarea.value.length);
+        }
+    }, 0);
+"#;
+
+/// Rendered HTML of a single line, without the paragraph around it.
+fn inline_html(text: &str) -> String {
+    let html = render_markdown(text);
+    let trimmed = html.trim();
+    trimmed
+        .strip_prefix("<p>")
+        .and_then(|inner| inner.strip_suffix("</p>"))
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+/// Live preview: every block renders as Markdown except the one being
+/// edited, which shows its source. Clicking a block edits it. Blocks carry
+/// the note line they start on in `data-line`.
+#[component]
+pub fn HybridView(id: String, content: String, on_change: EventHandler<String>) -> Element {
+    // Byte range of the block shown as source
+    let mut editing = use_signal(|| None::<Range<usize>>);
+
+    // Edits elsewhere (another note, find and replace) can leave it stale
+    let active = editing().filter(|range| {
+        range.end <= content.len() && content.is_char_boundary(range.start) && content.is_char_boundary(range.end)
+    });
+
+    // (range, shown as source)
+    let blocks: Vec<(Range<usize>, bool)> = match &active {
+        Some(region) => split_blocks(&content[..region.start])
+            .into_iter()
+            .map(|range| (range, false))
+            .chain(std::iter::once((region.clone(), true)))
+            .chain(
+                split_blocks(&content[region.end..])
+                    .into_iter()
+                    .map(|range| (range.start + region.end..range.end + region.end, false)),
+            )
+            .collect(),
+        None => split_blocks(&content).into_iter().map(|range| (range, false)).collect(),
+    };
+
+    let blocks: Vec<(Range<usize>, bool, String)> = blocks
+        .into_iter()
+        .map(|(range, is_source)| {
+            let text = content[range.clone()].to_string();
+            (range, is_source, text)
+        })
+        .collect();
+    let end_content = content.clone();
+
+    rsx! {
+        div { id: "{id}", class: "hybrid-view",
+            div { class: "hybrid-content",
+                for (range, is_source, text) in blocks {
+                    if is_source {
+                        textarea {
+                            key: "source-{range.start}",
+                            class: "hybrid-source",
+                            "data-line": content[..range.start].matches('\n').count(),
+                            rows: text.lines().count().max(1) as i64,
+                            value: "{text}",
+                            oninput: {
+                                let content = content.clone();
+                                let range = range.clone();
+                                move |evt: FormEvent| {
+                                    let value = evt.value();
+                                    let updated = format!("{}{}{}", &content[..range.start], value, &content[range.end..]);
+                                    editing.set(Some(range.start..range.start + value.len()));
+                                    on_change.call(updated);
+                                }
+                            },
+                            onblur: move |_| editing.set(None),
+                            onkeydown: move |evt: KeyboardEvent| {
+                                if evt.key() == Key::Escape {
+                                    evt.stop_propagation();
+                                    editing.set(None);
+                                }
+                            },
+                        }
+                    } else {
+                        HybridBlock {
+                            key: "{range.start}",
+                            text: text.clone(),
+                            first_line: content[..range.start].matches('\n').count(),
+                            on_edit: {
+                                let range = range.clone();
+                                move |_| {
+                                    editing.set(Some(range.clone()));
+                                    eval(FOCUS_SOURCE_JS);
+                                }
+                            },
+                            on_toggle_task: {
+                                let content = content.clone();
+                                move |line: usize| on_change.call(toggle_task(&content, line))
+                            },
+                        }
+                    }
+                }
+                div {
+                    class: "hybrid-end",
+                    onclick: move |_| {
+                        // Start a new block after the last one
+                        let updated = if end_content.trim().is_empty() {
+                            String::new()
+                        } else {
+                            format!("{}\n\n", end_content.trim_end())
+                        };
+                        let start = updated.len();
+                        if updated != end_content {
+                            on_change.call(updated);
+                        }
+                        editing.set(Some(start..start));
+                        eval(FOCUS_SOURCE_JS);
+                    },
+                    if content.trim().is_empty() && active.is_none() {
+                        span { class: "hybrid-placeholder", "Start writing..." }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn HybridBlock(
+    text: String,
+    // Line of the note the block starts on
+    first_line: usize,
+    on_edit: EventHandler<()>,
+    // Note line of the task to tick or clear
+    on_toggle_task: EventHandler<usize>,
+) -> Element {
+    let tasks = extract_tasks(&text);
+    let html = render_markdown(&text);
+
+    if tasks.is_empty() {
+        return rsx! {
+            div {
+                class: "hybrid-block",
+                "data-line": first_line,
+                onclick: move |_| on_edit.call(()),
+                if html.trim().is_empty() {
+                    // Front matter and other source that renders to nothing
+                    pre { class: "hybrid-raw", "{text}" }
+                } else {
+                    div { class: "preview-content", dangerous_inner_html: "{html}" }
+                }
+            }
+        };
+    }
+
+    // Task lists render line by line so each checkbox maps to its line
+    rsx! {
+        div {
+            class: "hybrid-block hybrid-tasks",
+            "data-line": first_line,
+            onclick: move |_| on_edit.call(()),
+            for (i, line, indent) in text.split('\n').enumerate().map(|(i, line)| (i, line, line.len() - line.trim_start().len())) {
+                if let Some(task) = tasks.iter().find(|task| task.line == i) {
+                    div {
+                        class: "hybrid-task",
+                        class: if task.done { "done" } else { "" },
+                        style: "padding-left: {indent}ch",
+                        input {
+                            r#type: "checkbox",
+                            checked: task.done,
+                            onclick: move |evt| {
+                                evt.stop_propagation();
+                                on_toggle_task.call(first_line + i);
+                            },
+                        }
+                        span { dangerous_inner_html: inline_html(&task.text) }
+                    }
+                } else if !line.trim().is_empty() {
+                    div { class: "preview-content", dangerous_inner_html: render_markdown(line) }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::state::commands::{Command, KeyContext, Shortcut};
+use crate::state::Keymap;
+use dioxus::prelude::*;
+
+/// The chord pressed in a keydown event. Cmd counts as Ctrl.
+pub fn shortcut_from_event(evt: &KeyboardEvent) -> Shortcut {
+    let modifiers = evt.modifiers();
+    let key = match evt.key() {
+        Key::Character(c) => c,
+        key => key.to_string(),
+    };
+    Shortcut::new(
+        modifiers.ctrl() || modifiers.meta(),
+        modifiers.shift(),
+        modifiers.alt(),
+        &key,
+    )
+}
+
+#[component]
+pub fn KeymapSettings(
+    keymap: Keymap,
+    on_change: EventHandler<Keymap>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut recording = use_signal(|| None::<Command>);
+    let mut filter = use_signal(String::new);
+
+    let query = filter().to_lowercase();
+    let sections = [(KeyContext::Global, "Global"), (KeyContext::Editor, "Editor")];
+    let conflicts: Vec<String> = keymap
+        .conflicts()
+        .iter()
+        .map(|conflict| {
+            format!(
+                "{} is bound to {}; the last one wins",
+                conflict.key.label(),
+                command_titles(&conflict.commands, " and ")
+            )
+        })
+        .collect();
+
+    let record_keymap = keymap.clone();
+    let reset_keymap = keymap.clone();
+
+    rsx! {
+        div {
+            class: "keymap-overlay",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "keymap-settings",
+                onclick: move |evt| evt.stop_propagation(),
+                onkeydown: move |evt: KeyboardEvent| {
+                    let Some(command) = recording() else {
+                        if evt.key() == Key::Escape {
+                            on_close.call(());
+                        }
+                        return;
+                    };
+                    // Keep the chord from also running a command
+                    evt.prevent_default();
+                    evt.stop_propagation();
+                    if evt.key() == Key::Escape {
+                        recording.set(None);
+                        return;
+                    }
+                    let shortcut = shortcut_from_event(&evt);
+                    if shortcut.is_valid_binding() {
+                        let mut updated = record_keymap.clone();
+                        updated.bind(command, shortcut);
+                        on_change.call(updated);
+                        recording.set(None);
+                    }
+                },
+
+                div { class: "keymap-header",
+                    span { class: "keymap-title", "Keyboard shortcuts" }
+                    input {
+                        class: "keymap-filter",
+                        r#type: "text",
+                        placeholder: "Filter commands...",
+                        value: "{filter}",
+                        oninput: move |evt| filter.set(evt.value()),
+                    }
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| {
+                            if reset_keymap != Keymap::default() {
+                                on_change.call(Keymap::default());
+                            }
+                        },
+                        title: "Restore the default shortcuts",
+                        "Reset"
+                    }
+                }
+
+                if !conflicts.is_empty() {
+                    div { class: "keymap-conflicts",
+                        for conflict in conflicts.iter() {
+                            div { "{conflict}" }
+                        }
+                    }
+                }
+
+                div { class: "keymap-list",
+                    for (context, heading) in sections {
+                        div { class: "keymap-section", "{heading}" }
+                        for command in Command::ALL.iter().copied().filter(|c| {
+                            c.context() == context && c.title().to_lowercase().contains(&query)
+                        }) {
+                            div {
+                                key: "{command:?}",
+                                class: "keymap-row",
+                                class: if recording() == Some(command) { "recording" } else { "" },
+                                span { class: "keymap-command", "{command.title()}" }
+                                div { class: "keymap-keys",
+                                    for (shortcut, others) in keymap
+                                        .shortcuts(command)
+                                        .into_iter()
+                                        .map(|shortcut| {
+                                            let others = keymap.conflicts_with(command, &shortcut);
+                                            (shortcut, others)
+                                        })
+                                    {
+                                        span {
+                                            class: "keymap-key",
+                                            class: if !others.is_empty() { "conflict" } else { "" },
+                                            title: if others.is_empty() { String::new() } else { format!("Also bound to {}", command_titles(&others, ", ")) },
+                                            kbd { "{shortcut.label()}" }
+                                            button {
+                                                class: "keymap-remove",
+                                                onclick: {
+                                                    let keymap = keymap.clone();
+                                                    move |_| {
+                                                        let mut updated = keymap.clone();
+                                                        updated.unbind(command, &shortcut);
+                                                        on_change.call(updated);
+                                                    }
+                                                },
+                                                title: "Remove shortcut",
+                                                "×"
+                                            }
+                                        }
+                                    }
+                                    button {
+                                        class: "keymap-add",
+                                        onclick: move |_| {
+                                            recording.set(if recording() == Some(command) { None } else { Some(command) });
+                                        },
+                                        if recording() == Some(command) { "Press keys…" } else { "+" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn command_titles(commands: &[Command], separator: &str) -> String {
+    commands
+        .iter()
+        .map(|command| command.title())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
@@ -1,12 +1,22 @@
 pub mod app;
+pub mod backlinks;
+pub mod command_palette;
+pub mod daily_calendar;
 pub mod editor;
+pub mod find_bar;
+pub mod find_replace;
+pub mod graph_view;
+pub mod hybrid_view;
+pub mod keymap_settings;
 pub mod note_item;
 pub mod outline;
 pub mod preview;
 pub mod quick_switcher;
+pub mod search_result;
 pub mod sidebar;
+pub mod smart_folders;
 pub mod status_bar;
+pub mod tab_bar;
 pub mod toast;
-pub mod toolbar;
 
 pub use app::App;
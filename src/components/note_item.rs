@@ -3,7 +3,12 @@ use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
 
 #[component]
-pub fn NoteItem(note: NoteSummary, is_selected: bool, on_click: EventHandler<String>) -> Element {
+pub fn NoteItem(
+    note: NoteSummary,
+    is_selected: bool,
+    on_click: EventHandler<String>,
+    #[props(default)] on_open_in_tab: EventHandler<String>,
+) -> Element {
     let id = note.id.clone();
     let class_name = if is_selected {
         "note-item selected"
@@ -16,8 +21,23 @@ pub fn NoteItem(note: NoteSummary, is_selected: bool, on_click: EventHandler<Str
     rsx! {
         div {
             class: class_name,
-            onclick: move |_| on_click.call(id.clone()),
-            div { class: "note-title", {title} }
+            onclick: move |evt: MouseEvent| {
+                let modifiers = evt.modifiers();
+                if modifiers.ctrl() || modifiers.meta() {
+                    on_open_in_tab.call(id.clone());
+                } else {
+                    on_click.call(id.clone());
+                }
+            },
+            div { class: "note-title",
+                {title}
+                if note.pinned {
+                    span { class: "note-badge", "Pinned" }
+                }
+                if note.is_template {
+                    span { class: "note-badge", "Template" }
+                }
+            }
             div { class: "note-date", {date} }
         }
     }
@@ -1,53 +1,129 @@
+use crate::markdown::{extract_headings, move_section, section_at, section_end, section_word_counts};
 use dioxus::prelude::*;
-
-#[derive(Clone, PartialEq)]
-pub struct HeadingInfo {
-    pub level: u8,
-    pub text: String,
-    pub line: usize,
-}
-
-pub fn extract_headings(content: &str) -> Vec<HeadingInfo> {
-    content
-        .lines()
-        .enumerate()
-        .filter_map(|(line_num, line)| {
-            let trimmed = line.trim_start();
-            if trimmed.starts_with('#') {
-                let level = trimmed.chars().take_while(|c| *c == '#').count() as u8;
-                if level <= 6 {
-                    let text = trimmed[level as usize..].trim().to_string();
-                    if !text.is_empty() {
-                        return Some(HeadingInfo {
-                            level,
-                            text,
-                            line: line_num,
-                        });
-                    }
-                }
-            }
-            None
-        })
-        .collect()
-}
+use std::collections::HashSet;
 
 #[component]
-pub fn Outline(content: String, on_jump: EventHandler<usize>) -> Element {
+pub fn Outline(
+    content: String,
+    on_jump: EventHandler<usize>,
+    // Line of the caret, or of the text being read, in `content`
+    #[props(default)] current_line: Option<usize>,
+    // The restructured note after a section was dragged to another place
+    #[props(default)] on_change: EventHandler<String>,
+) -> Element {
     let headings = extract_headings(&content);
+    let word_counts = section_word_counts(&content, &headings);
+    let line_count = content.split('\n').count();
+    // Collapsed headings by level and text, which survive edits above them
+    let mut collapsed = use_signal(HashSet::<(u8, String)>::new);
+    // Heading index being dragged, and the line it would be dropped before
+    let mut dragging = use_signal(|| None::<usize>);
+    let mut drop_target = use_signal(|| None::<usize>);
+
+    let current = current_line.and_then(|line| section_at(&headings, line));
+    let mut items = Vec::new();
+    // End line of the collapsed section being skipped
+    let mut hidden_until = None;
+    for (i, heading) in headings.iter().enumerate() {
+        if hidden_until.is_some_and(|end| heading.line < end) {
+            continue;
+        }
+        hidden_until = None;
+        let end = section_end(&headings, i, line_count);
+        let has_children = headings.get(i + 1).is_some_and(|next| next.line < end);
+        let is_collapsed = has_children && collapsed.read().contains(&(heading.level, heading.text.clone()));
+        if is_collapsed {
+            hidden_until = Some(end);
+        }
+        // A collapsed heading stands in for the sections folded into it
+        let is_current = current.is_some_and(|c| c == i || (is_collapsed && c > i && headings[c].line < end));
+        items.push((i, heading.clone(), word_counts[i], has_children, is_collapsed, is_current));
+    }
+
+    let drop_section = move |to: usize| {
+        if let Some(from) = dragging()
+            && let Some(moved) = move_section(&content, &headings, from, to)
+        {
+            on_change.call(moved);
+        }
+        dragging.set(None);
+        drop_target.set(None);
+    };
 
     rsx! {
         aside { class: "outline",
             div { class: "outline-track",
-                for heading in headings {
+                for (i, heading, words, has_children, is_collapsed, is_current) in items {
                     div {
                         class: "outline-item level-{heading.level}",
+                        class: if is_current { "current" } else { "" },
+                        class: if drop_target() == Some(heading.line) { "outline-drop-target" } else { "" },
                         title: "{heading.text}",
+                        draggable: "true",
+                        ondragstart: move |_| dragging.set(Some(i)),
+                        ondragover: {
+                            let line = heading.line;
+                            move |evt: DragEvent| {
+                                evt.prevent_default();
+                                drop_target.set(Some(line));
+                            }
+                        },
+                        ondragleave: move |_| drop_target.set(None),
+                        ondrop: {
+                            let line = heading.line;
+                            let mut drop_section = drop_section.clone();
+                            move |evt: DragEvent| {
+                                evt.prevent_default();
+                                drop_section(line);
+                            }
+                        },
+                        ondragend: move |_| {
+                            dragging.set(None);
+                            drop_target.set(None);
+                        },
                         onclick: {
                             let line = heading.line;
                             move |_| on_jump.call(line)
                         },
                         span { class: "outline-indicator" }
+                        if has_children {
+                            button {
+                                class: "outline-toggle",
+                                title: if is_collapsed { "Expand" } else { "Collapse" },
+                                onclick: {
+                                    let key = (heading.level, heading.text.clone());
+                                    move |evt: MouseEvent| {
+                                        evt.stop_propagation();
+                                        let mut collapsed = collapsed.write();
+                                        if !collapsed.remove(&key) {
+                                            collapsed.insert(key.clone());
+                                        }
+                                    }
+                                },
+                                if is_collapsed { "▸" } else { "▾" }
+                            }
+                        }
                         span { class: "outline-text", "{heading.text}" }
+                        span { class: "outline-count", title: "Words in this section", "{words}" }
+                    }
+                }
+                if dragging().is_some() {
+                    // Dropping here moves the section to the end of the note
+                    div {
+                        class: "outline-drop-end",
+                        class: if drop_target() == Some(line_count) { "outline-drop-target" } else { "" },
+                        ondragover: move |evt: DragEvent| {
+                            evt.prevent_default();
+                            drop_target.set(Some(line_count));
+                        },
+                        ondragleave: move |_| drop_target.set(None),
+                        ondrop: {
+                            let mut drop_section = drop_section.clone();
+                            move |evt: DragEvent| {
+                                evt.prevent_default();
+                                drop_section(line_count);
+                            }
+                        },
                     }
                 }
             }
@@ -1,30 +1,104 @@
+use crate::components::search_result::{SearchError, Snippet};
+use crate::markdown::{extract_headings, HeadingInfo};
+use crate::state::fuzzy::{fuzzy_match, highlight_indices};
 use crate::state::note::NoteSummary;
+use crate::state::query::parse_query;
+use crate::state::{QueryError, SearchHit};
 use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
 
+// Ranking of title matches
+const RECENCY_BONUS: f64 = 24.0;
+const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+const MAX_RESULTS: usize = 100;
+// Notes searched for headings with `Note#Heading`
+const HEADING_NOTES_LIMIT: usize = 5;
+
+/// A heading found with `Note#Heading`
+#[derive(Clone, PartialEq)]
+pub struct HeadingHit {
+    pub note_id: String,
+    pub note_title: String,
+    pub heading: HeadingInfo,
+    /// Matched char indices in the heading text
+    pub indices: Vec<usize>,
+}
+
 #[component]
 pub fn QuickSwitcher(
     notes: Vec<NoteSummary>,
+    #[props(default)] templates: Vec<NoteSummary>,
+    #[props(default)] initial_query: String,
+    #[props(default)] content_results: Vec<SearchHit>,
+    #[props(default)] search_error: Option<QueryError>,
+    #[props(default)] heading_results: Vec<HeadingHit>,
+    #[props(default)] on_query_change: EventHandler<String>,
     on_select: EventHandler<String>,
+    on_open_result: EventHandler<(String, usize)>,
+    on_new_from_template: EventHandler<String>,
+    on_create: EventHandler<String>,
     on_close: EventHandler<()>,
 ) -> Element {
-    let mut query = use_signal(String::new);
+    let mut query = use_signal(|| initial_query.clone());
     let mut selected_index = use_signal(|| 0usize);
 
-    let q = query().to_lowercase();
-    let filtered_notes: Vec<NoteSummary> = if q.is_empty() {
-        notes.clone()
+    // A leading "+" lists templates to start a new note from
+    let raw_query = query();
+    let is_template_mode = raw_query.starts_with('+');
+    let q = raw_query.trim_start_matches('+').to_lowercase();
+    // "Note#Heading" lists headings inside the matching notes
+    let is_heading_mode = !is_template_mode && split_heading_query(&raw_query).is_some();
+    // Filters and operators go straight to the search index
+    let is_structured = !is_template_mode
+        && !is_heading_mode
+        && !parse_query(&raw_query).is_ok_and(|query| query.is_plain_text());
+    let candidates = if is_template_mode { &templates } else { &notes };
+    // Titles still match in heading mode, for names like "C# notes"
+    let filtered_notes: Vec<NoteSummary> = if is_structured {
+        Vec::new()
+    } else {
+        rank_titles(candidates, &q)
+    };
+    let matched_chars: Vec<Vec<usize>> = filtered_notes
+        .iter()
+        .map(|n| fuzzy_match(&n.title, &q).map(|m| m.indices).unwrap_or_default())
+        .collect();
+    let pick = move |id: String| {
+        if is_template_mode {
+            on_new_from_template.call(id);
+        } else {
+            on_select.call(id);
+        }
+    };
+
+    // Notes whose contents match, below the title matches
+    let content_hits: Vec<SearchHit> = if is_template_mode || is_heading_mode {
+        Vec::new()
     } else {
-        notes
+        content_results
             .iter()
-            .filter(|n| fuzzy_match(&n.title.to_lowercase(), &q))
+            .filter(|hit| !filtered_notes.iter().any(|n| n.id == hit.id))
             .cloned()
             .collect()
     };
 
-    let filtered_len = filtered_notes.len();
+    let heading_hits: Vec<HeadingHit> = if is_heading_mode {
+        heading_results.clone()
+    } else {
+        Vec::new()
+    };
+
+    let title_len = filtered_notes.len();
+    let content_len = content_hits.len();
+    let filtered_len = title_len + content_len + heading_hits.len();
     let current_selected = selected_index().min(filtered_len.saturating_sub(1));
 
+    // With nothing to open, Enter creates a note named after the query;
+    // filters and queries that don't parse aren't titles
+    let new_title = raw_query.trim().to_string();
+    let can_create = !is_template_mode && !is_heading_mode && !is_structured && !new_title.is_empty();
+    let create_title = new_title.clone();
+
     rsx! {
         div {
             class: "quick-switcher",
@@ -35,28 +109,33 @@ pub fn QuickSwitcher(
                 onclick: move |evt| evt.stop_propagation(),
                 onkeydown: {
                     let filtered_notes = filtered_notes.clone();
+                    let content_hits = content_hits.clone();
+                    let heading_hits = heading_hits.clone();
                     move |evt: KeyboardEvent| {
                         match evt.key() {
                             Key::Escape => on_close.call(()),
-                            Key::ArrowDown => {
-                                if filtered_len > 0 {
-                                    selected_index.set((current_selected + 1) % filtered_len);
-                                }
+                            Key::ArrowDown if filtered_len > 0 => {
+                                selected_index.set((current_selected + 1) % filtered_len);
                             }
-                            Key::ArrowUp => {
-                                if filtered_len > 0 {
-                                    selected_index.set(
-                                        if current_selected == 0 {
-                                            filtered_len.saturating_sub(1)
-                                        } else {
-                                            current_selected - 1
-                                        }
-                                    );
-                                }
+                            Key::ArrowUp if filtered_len > 0 => {
+                                selected_index.set(
+                                    if current_selected == 0 {
+                                        filtered_len.saturating_sub(1)
+                                    } else {
+                                        current_selected - 1
+                                    }
+                                );
                             }
                             Key::Enter => {
-                                if let Some(note) = filtered_notes.get(current_selected) {
-                                    on_select.call(note.id.clone());
+                                // Shift+Enter always creates, even when something matches
+                                if can_create && (evt.modifiers().shift() || filtered_len == 0) {
+                                    on_create.call(new_title.clone());
+                                } else if let Some(note) = filtered_notes.get(current_selected) {
+                                    pick(note.id.clone());
+                                } else if let Some(hit) = content_hits.get(current_selected - title_len) {
+                                    on_open_result.call((hit.id.clone(), hit.line));
+                                } else if let Some(hit) = heading_hits.get(current_selected - title_len - content_len) {
+                                    on_open_result.call((hit.note_id.clone(), hit.heading.line));
                                 }
                             }
                             _ => {}
@@ -67,15 +146,20 @@ pub fn QuickSwitcher(
                 input {
                     class: "quick-switcher-input",
                     r#type: "text",
-                    placeholder: "Search notes...",
+                    placeholder: "Search notes... (+ for templates, Note#Heading for headings)",
                     autofocus: true,
                     value: "{query}",
                     oninput: move |evt| {
+                        on_query_change.call(evt.value());
                         query.set(evt.value());
                         selected_index.set(0);
                     },
                 }
 
+                if let Some(error) = search_error.clone() {
+                    SearchError { query: raw_query.clone(), error }
+                }
+
                 div { class: "quick-switcher-results",
                     for (idx, note) in filtered_notes.iter().enumerate() {
                         div {
@@ -83,14 +167,75 @@ pub fn QuickSwitcher(
                             class: if idx == current_selected { "quick-switcher-item selected" } else { "quick-switcher-item" },
                             onclick: {
                                 let id = note.id.clone();
-                                move |_| on_select.call(id.clone())
+                                move |_| pick(id.clone())
                             },
-                            span { class: "quick-switcher-title", "{note.title}" }
-                            span { class: "quick-switcher-meta", "{format_relative_time(note.updated_at)}" }
+                            span { class: "quick-switcher-title",
+                                for (text, is_match) in highlight_indices(&note.title, &matched_chars[idx]) {
+                                    if is_match {
+                                        mark { "{text}" }
+                                    } else {
+                                        "{text}"
+                                    }
+                                }
+                            }
+                            if is_template_mode {
+                                span { class: "quick-switcher-meta", "New from template" }
+                            } else {
+                                span { class: "quick-switcher-meta", "{format_relative_time(note.updated_at)}" }
+                            }
                         }
                     }
-                    if filtered_notes.is_empty() {
-                        div { class: "quick-switcher-empty", "No notes found" }
+                    if !content_hits.is_empty() {
+                        div { class: "quick-switcher-section",
+                            if is_structured { "Matching notes" } else { "In note contents" }
+                        }
+                    }
+                    for (idx, hit) in content_hits.iter().enumerate() {
+                        div {
+                            key: "content-{hit.id}",
+                            class: if title_len + idx == current_selected { "quick-switcher-item selected" } else { "quick-switcher-item" },
+                            onclick: {
+                                let target = (hit.id.clone(), hit.line);
+                                move |_| on_open_result.call(target.clone())
+                            },
+                            div { class: "quick-switcher-hit",
+                                span { class: "quick-switcher-title", "{hit.title}" }
+                                Snippet { parts: hit.snippet.clone() }
+                            }
+                            span { class: "quick-switcher-meta", "{format_relative_time(hit.updated_at)}" }
+                        }
+                    }
+                    for (idx, hit) in heading_hits.iter().enumerate() {
+                        div {
+                            key: "heading-{hit.note_id}-{hit.heading.line}",
+                            class: if title_len + content_len + idx == current_selected { "quick-switcher-item selected" } else { "quick-switcher-item" },
+                            onclick: {
+                                let target = (hit.note_id.clone(), hit.heading.line);
+                                move |_| on_open_result.call(target.clone())
+                            },
+                            span { class: "quick-switcher-title quick-switcher-heading level-{hit.heading.level}",
+                                for (text, is_match) in highlight_indices(&hit.heading.text, &hit.indices) {
+                                    if is_match {
+                                        mark { "{text}" }
+                                    } else {
+                                        "{text}"
+                                    }
+                                }
+                            }
+                            span { class: "quick-switcher-meta", "{hit.note_title}" }
+                        }
+                    }
+                    if filtered_len == 0 && can_create {
+                        div {
+                            class: "quick-switcher-item quick-switcher-create selected",
+                            onclick: move |_| on_create.call(create_title.clone()),
+                            span { class: "quick-switcher-title", "Create \"{create_title}\"" }
+                            span { class: "quick-switcher-meta", "Enter" }
+                        }
+                    } else if filtered_len == 0 {
+                        div { class: "quick-switcher-empty",
+                            if is_template_mode { "No templates found" } else { "No notes found" }
+                        }
                     }
                 }
             }
@@ -98,18 +243,81 @@ pub fn QuickSwitcher(
     }
 }
 
-fn fuzzy_match(text: &str, query: &str) -> bool {
-    let mut text_chars = text.chars().peekable();
-    for query_char in query.chars() {
-        loop {
-            match text_chars.next() {
-                Some(c) if c == query_char => break,
-                Some(_) => continue,
-                None => return false,
-            }
+/// Notes whose titles fuzzy-match `query`, best first. Closer matches win,
+/// with recently edited notes nudged ahead of stale ones.
+fn rank_titles(candidates: &[NoteSummary], query: &str) -> Vec<NoteSummary> {
+    if query.trim().is_empty() {
+        return candidates.iter().take(MAX_RESULTS).cloned().collect();
+    }
+
+    let now = Utc::now();
+    let mut ranked: Vec<(f64, &NoteSummary)> = candidates
+        .iter()
+        .filter_map(|note| {
+            let matched = fuzzy_match(&note.title, query)?;
+            let age_days = now.signed_duration_since(note.updated_at).num_hours() as f64 / 24.0;
+            let recency = RECENCY_BONUS * (-age_days.max(0.0) / RECENCY_HALF_LIFE_DAYS).exp();
+            Some((matched.score as f64 + recency, note))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, note)| note.clone())
+        .collect()
+}
+
+/// Splits `Note#Heading` into its note and heading parts. Queries with
+/// filters, like `tag:#work`, are searches rather than headings.
+fn split_heading_query(query: &str) -> Option<(&str, &str)> {
+    let (note, heading) = query.split_once('#')?;
+    let is_key = note.split_whitespace().last().is_some_and(|word| word.contains(':'));
+    if note.trim().is_empty() || is_key || !parse_query(query).is_ok_and(|query| query.is_plain_text()) {
+        return None;
+    }
+    Some((note.trim(), heading.trim()))
+}
+
+/// Headings matching a `Note#Heading` query, inside the notes whose titles
+/// match best. `content` looks up a note's text by id.
+pub fn find_headings(
+    notes: &[NoteSummary],
+    query: &str,
+    content: impl Fn(&str) -> Option<String>,
+) -> Vec<HeadingHit> {
+    let Some((note_query, heading_query)) = split_heading_query(query) else {
+        return Vec::new();
+    };
+
+    let mut hits = Vec::new();
+    for note in rank_titles(notes, &note_query.to_lowercase()).iter().take(HEADING_NOTES_LIMIT) {
+        let Some(text) = content(&note.id) else { continue };
+        let mut matches: Vec<(i64, HeadingHit)> = extract_headings(&text)
+            .into_iter()
+            .filter_map(|heading| {
+                let matched = fuzzy_match(&heading.text, heading_query)?;
+                Some((
+                    matched.score,
+                    HeadingHit {
+                        note_id: note.id.clone(),
+                        note_title: note.title.clone(),
+                        heading,
+                        indices: matched.indices,
+                    },
+                ))
+            })
+            .collect();
+        // Document order when only the note is given
+        if !heading_query.is_empty() {
+            matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
         }
+        hits.extend(matches.into_iter().map(|(_, hit)| hit));
     }
-    true
+
+    hits.truncate(MAX_RESULTS);
+    hits
 }
 
 fn format_relative_time(dt: DateTime<Utc>) -> String {
@@ -132,3 +340,23 @@ fn format_relative_time(dt: DateTime<Utc>) -> String {
         dt.format("%b %d").to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_note_and_heading() {
+        assert_eq!(split_heading_query("Plan # Goals"), Some(("Plan", "Goals")));
+        assert_eq!(split_heading_query("Plan#"), Some(("Plan", "")));
+        assert_eq!(split_heading_query("#Goals"), None);
+    }
+
+    #[test]
+    fn filters_with_hashes_stay_searches() {
+        assert_eq!(split_heading_query("tag:#work"), None);
+        assert_eq!(split_heading_query("meeting tag:#work"), None);
+        assert_eq!(split_heading_query("todo:#later"), None);
+        assert_eq!(split_heading_query("-draft Plan#Goals"), None);
+    }
+}
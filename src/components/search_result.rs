@@ -0,0 +1,55 @@
+use crate::state::{QueryError, SearchHit};
+use dioxus::prelude::*;
+
+/// A line of note content with the matched words highlighted.
+#[component]
+pub fn Snippet(parts: Vec<(String, bool)>) -> Element {
+    rsx! {
+        span { class: "search-snippet",
+            for (text, is_match) in parts {
+                if is_match {
+                    mark { "{text}" }
+                } else {
+                    "{text}"
+                }
+            }
+        }
+    }
+}
+
+/// A search that didn't parse, with the token at fault marked.
+#[component]
+pub fn SearchError(query: String, error: QueryError) -> Element {
+    let (before, token, after) = error.split_query(&query);
+
+    rsx! {
+        div { class: "search-error",
+            div { class: "search-error-query",
+                "{before}"
+                mark { "{token}" }
+                "{after}"
+            }
+            "{error.message}"
+        }
+    }
+}
+
+#[component]
+pub fn SearchResult(hit: SearchHit, is_selected: bool, on_open: EventHandler<(String, usize)>) -> Element {
+    let id = hit.id.clone();
+    let line = hit.line;
+    let class_name = if is_selected {
+        "search-result selected"
+    } else {
+        "search-result"
+    };
+
+    rsx! {
+        div {
+            class: class_name,
+            onclick: move |_| on_open.call((id.clone(), line)),
+            div { class: "note-title", "{hit.title}" }
+            Snippet { parts: hit.snippet.clone() }
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use crate::components::backlinks::Backlinks;
 use crate::components::note_item::NoteItem;
-use crate::state::NoteSummary;
+use crate::components::search_result::{SearchError, SearchResult};
+use crate::components::smart_folders::SmartFolders;
+use crate::state::{Backlink, NoteSummary, QueryError, SavedSearch, SearchHit};
 use dioxus::prelude::*;
 
 #[component]
@@ -7,23 +10,36 @@ pub fn Sidebar(
     notes: Vec<NoteSummary>,
     current_id: Option<String>,
     on_select: EventHandler<String>,
+    // Ctrl+click: open the note in a new tab
+    #[props(default)] on_open_in_tab: EventHandler<String>,
     on_new: EventHandler<()>,
+    #[props(default)] search_query: String,
+    #[props(default)] search_results: Vec<SearchHit>,
+    #[props(default)] search_error: Option<QueryError>,
+    on_search_change: EventHandler<String>,
+    on_open_result: EventHandler<(String, usize)>,
+    #[props(default)] backlinks: Vec<Backlink>,
+    on_open_backlink: EventHandler<(String, usize)>,
+    #[props(default)] templates: Vec<NoteSummary>,
+    #[props(default)] default_template: Option<String>,
+    on_new_from_template: EventHandler<String>,
+    on_set_default_template: EventHandler<Option<String>>,
+    #[props(default)] smart_folders: Vec<(SavedSearch, Vec<SearchHit>)>,
+    // Name for the current search query
+    on_save_search: EventHandler<String>,
+    on_delete_search: EventHandler<String>,
 ) -> Element {
-    let mut search_query = use_signal(String::new);
+    let mut is_template_menu_open = use_signal(|| false);
+    let mut save_name = use_signal(|| None::<String>);
 
-    let filtered_notes: Vec<_> = notes
-        .iter()
-        .filter(|note| {
-            let query = search_query.read().to_lowercase();
-            query.is_empty() || note.title.to_lowercase().contains(&query)
-        })
-        .cloned()
-        .collect();
-
-    let filtered_count = filtered_notes.len();
+    let result_count = search_results.len();
     let total_count = notes.len();
-    let has_query = !search_query.read().is_empty();
-    let is_empty = filtered_notes.is_empty();
+    let has_query = !search_query.trim().is_empty();
+    let is_empty = if has_query {
+        search_results.is_empty()
+    } else {
+        notes.is_empty()
+    };
 
     rsx! {
         aside { class: "sidebar",
@@ -34,35 +50,90 @@ pub fn Sidebar(
                 input {
                     class: "sidebar-search-input",
                     r#type: "text",
-                    placeholder: "Search notes...",
+                    placeholder: "Search notes... (tag:, in:, is:pinned)",
                     value: "{search_query}",
-                    oninput: move |e| search_query.set(e.value()),
+                    oninput: move |e| on_search_change.call(e.value()),
                 }
                 if has_query {
                     button {
                         class: "sidebar-search-clear",
-                        onclick: move |_| search_query.set(String::new()),
+                        onclick: move |_| on_search_change.call(String::new()),
                         "×"
                     }
                 }
             }
-            if has_query {
+            if let Some(error) = search_error.clone() {
+                SearchError { query: search_query.clone(), error }
+            } else if has_query {
                 div { class: "sidebar-search-count",
-                    "{filtered_count} of {total_count} notes"
+                    "{result_count} of {total_count} notes"
+                    if save_name().is_none() {
+                        button {
+                            class: "sidebar-save-search",
+                            onclick: move |_| save_name.set(Some(String::new())),
+                            title: "Save this search as a smart folder",
+                            "Save"
+                        }
+                    }
+                }
+                if let Some(name) = save_name() {
+                    div { class: "sidebar-save-form",
+                        input {
+                            class: "sidebar-save-input",
+                            r#type: "text",
+                            placeholder: "Smart folder name",
+                            autofocus: true,
+                            value: "{name}",
+                            oninput: move |e| save_name.set(Some(e.value())),
+                            onkeydown: move |e: KeyboardEvent| match e.key() {
+                                Key::Enter => {
+                                    if let Some(name) = save_name().filter(|n| !n.trim().is_empty()) {
+                                        on_save_search.call(name);
+                                        save_name.set(None);
+                                    }
+                                }
+                                Key::Escape => save_name.set(None),
+                                _ => {}
+                            },
+                        }
+                    }
                 }
             }
             div { class: "notes-list",
-                for note in filtered_notes {
-                    NoteItem {
-                        key: "{note.id}",
-                        note: note.clone(),
-                        is_selected: current_id.as_ref() == Some(&note.id),
-                        on_click: move |id| on_select.call(id),
+                if !has_query && !smart_folders.is_empty() {
+                    SmartFolders {
+                        folders: smart_folders.clone(),
+                        current_id: current_id.clone(),
+                        on_open: move |id| on_select.call(id),
+                        on_edit: move |query| on_search_change.call(query),
+                        on_delete: move |name| on_delete_search.call(name),
+                    }
+                }
+                if has_query {
+                    for hit in search_results.iter().cloned() {
+                        SearchResult {
+                            key: "{hit.id}",
+                            is_selected: current_id.as_ref() == Some(&hit.id),
+                            hit: hit.clone(),
+                            on_open: move |target| on_open_result.call(target),
+                        }
+                    }
+                } else {
+                    for note in notes.iter().cloned() {
+                        NoteItem {
+                            key: "{note.id}",
+                            note: note.clone(),
+                            is_selected: current_id.as_ref() == Some(&note.id),
+                            on_click: move |id| on_select.call(id),
+                            on_open_in_tab: move |id| on_open_in_tab.call(id),
+                        }
                     }
                 }
                 if is_empty {
                     div { class: "empty-state",
-                        if has_query {
+                        if search_error.is_some() {
+                            "Fix the search to see results"
+                        } else if has_query {
                             "No matching notes"
                         } else {
                             "No notes yet"
@@ -70,6 +141,50 @@ pub fn Sidebar(
                     }
                 }
             }
+            if current_id.is_some() {
+                Backlinks {
+                    backlinks: backlinks.clone(),
+                    on_open: move |target| on_open_backlink.call(target),
+                }
+            }
+            if is_template_menu_open() {
+                div { class: "template-menu",
+                    for template in templates.iter().cloned() {
+                        div {
+                            key: "{template.id}",
+                            class: "template-menu-item",
+                            span {
+                                class: "template-menu-title",
+                                onclick: {
+                                    let id = template.id.clone();
+                                    move |_| {
+                                        is_template_menu_open.set(false);
+                                        on_new_from_template.call(id.clone());
+                                    }
+                                },
+                                "{template.title}"
+                            }
+                            button {
+                                class: if default_template.as_ref() == Some(&template.id) { "btn-bar active" } else { "btn-bar" },
+                                onclick: {
+                                    let id = template.id.clone();
+                                    let is_default = default_template.as_ref() == Some(&template.id);
+                                    move |_| {
+                                        on_set_default_template.call(if is_default { None } else { Some(id.clone()) });
+                                    }
+                                },
+                                title: "Use for Ctrl+N",
+                                "★"
+                            }
+                        }
+                    }
+                    if templates.is_empty() {
+                        div { class: "template-menu-empty",
+                            "Mark a note as a template to start new notes from it"
+                        }
+                    }
+                }
+            }
             div { class: "sidebar-footer",
                 button {
                     class: "btn-new-full",
@@ -77,6 +192,12 @@ pub fn Sidebar(
                     title: "New Note (Ctrl+N)",
                     "+ New Note"
                 }
+                button {
+                    class: if is_template_menu_open() { "btn-template active" } else { "btn-template" },
+                    onclick: move |_| is_template_menu_open.set(!is_template_menu_open()),
+                    title: "New note from template (Ctrl+Shift+N)",
+                    "⧉"
+                }
             }
         }
     }
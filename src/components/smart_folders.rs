@@ -0,0 +1,87 @@
+use crate::state::{SavedSearch, SearchHit};
+use dioxus::prelude::*;
+use std::collections::HashSet;
+
+#[component]
+pub fn SmartFolders(
+    folders: Vec<(SavedSearch, Vec<SearchHit>)>,
+    current_id: Option<String>,
+    on_open: EventHandler<String>,
+    // Loads the folder's query into the search box for editing
+    on_edit: EventHandler<String>,
+    on_delete: EventHandler<String>,
+) -> Element {
+    let mut expanded = use_signal(HashSet::<String>::new);
+
+    rsx! {
+        div { class: "smart-folders",
+            div { class: "smart-folders-header", "Smart folders" }
+            for (saved, hits) in folders {
+                div {
+                    key: "{saved.name}",
+                    class: "smart-folder",
+                    div {
+                        class: "smart-folder-row",
+                        title: "{saved.query}",
+                        onclick: {
+                            let name = saved.name.clone();
+                            move |_| {
+                                let mut set = expanded();
+                                if !set.remove(&name) {
+                                    set.insert(name.clone());
+                                }
+                                expanded.set(set);
+                            }
+                        },
+                        span { class: "smart-folder-toggle",
+                            if expanded().contains(&saved.name) { "▾" } else { "▸" }
+                        }
+                        span { class: "smart-folder-name", "{saved.name}" }
+                        span { class: "smart-folder-count", "{hits.len()}" }
+                        button {
+                            class: "smart-folder-action",
+                            onclick: {
+                                let query = saved.query.clone();
+                                move |evt: MouseEvent| {
+                                    evt.stop_propagation();
+                                    on_edit.call(query.clone());
+                                }
+                            },
+                            title: "Edit search",
+                            "⌕"
+                        }
+                        button {
+                            class: "smart-folder-action",
+                            onclick: {
+                                let name = saved.name.clone();
+                                move |evt: MouseEvent| {
+                                    evt.stop_propagation();
+                                    on_delete.call(name.clone());
+                                }
+                            },
+                            title: "Remove smart folder",
+                            "×"
+                        }
+                    }
+                    if expanded().contains(&saved.name) {
+                        for hit in hits.iter() {
+                            div {
+                                key: "{saved.name}-{hit.id}",
+                                class: "smart-folder-note",
+                                class: if current_id.as_ref() == Some(&hit.id) { "selected" } else { "" },
+                                onclick: {
+                                    let id = hit.id.clone();
+                                    move |_| on_open.call(id.clone())
+                                },
+                                "{hit.title}"
+                            }
+                        }
+                        if hits.is_empty() {
+                            div { class: "smart-folder-empty", "No matching notes" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::state::commands::Command;
+use crate::state::Keymap;
 use dioxus::prelude::*;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -7,26 +9,79 @@ pub enum TimerState {
     Paused,
 }
 
-const PRESET_DURATIONS: &[(u64, &str)] = &[
-    (5 * 60, "5m"),
-    (15 * 60, "15m"),
-    (25 * 60, "25m"),
-    (45 * 60, "45m"),
+const PRESET_COMMANDS: &[(Command, &str)] = &[
+    (Command::Timer5, "5m"),
+    (Command::Timer15, "15m"),
+    (Command::Timer25, "25m"),
+    (Command::Timer45, "45m"),
 ];
 
+/// Focus timer, owned by `App` so commands can drive it
+#[derive(Clone, Copy, PartialEq)]
+pub struct Timer {
+    pub remaining_seconds: u64,
+    pub initial_seconds: u64,
+    pub state: TimerState,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self {
+            remaining_seconds: 25 * 60,
+            initial_seconds: 25 * 60,
+            state: TimerState::Stopped,
+        }
+    }
+}
+
+impl Timer {
+    pub fn set_duration(&mut self, seconds: u64) {
+        self.initial_seconds = seconds;
+        self.remaining_seconds = seconds;
+        self.state = TimerState::Stopped;
+    }
+
+    pub fn start_or_pause(&mut self) {
+        if self.state == TimerState::Running {
+            self.state = TimerState::Paused;
+        } else {
+            if self.remaining_seconds == 0 {
+                self.remaining_seconds = self.initial_seconds;
+            }
+            self.state = TimerState::Running;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.remaining_seconds = self.initial_seconds;
+        self.state = TimerState::Stopped;
+    }
+
+    fn tick(&mut self) {
+        if self.state == TimerState::Running && self.remaining_seconds > 0 {
+            self.remaining_seconds -= 1;
+            if self.remaining_seconds == 0 {
+                self.state = TimerState::Stopped;
+            }
+        }
+    }
+}
+
 #[component]
 pub fn StatusBar(
     #[props(default)] content: Option<String>,
     #[props(default)] has_note: bool,
     #[props(default)] is_sidebar_visible: bool,
     #[props(default)] is_preview_visible: bool,
+    #[props(default)] is_hybrid: bool,
     #[props(default)] is_focus_mode: bool,
-    #[props(default)] font_size: u8,
-    on_toggle_sidebar: EventHandler<()>,
-    on_toggle_preview: EventHandler<()>,
-    on_toggle_focus: EventHandler<()>,
-    on_delete: EventHandler<()>,
-    on_font_size_change: EventHandler<u8>,
+    // Vim mode and pending keys, when Vim keybindings are on
+    #[props(default)] vim_status: Option<String>,
+    #[props(default)] is_template: bool,
+    #[props(default)] is_pinned: bool,
+    timer: Signal<Timer>,
+    keymap: Keymap,
+    on_command: EventHandler<Command>,
 ) -> Element {
     let (word_count, _reading_time) = content
         .as_ref()
@@ -37,13 +92,10 @@ pub fn StatusBar(
         })
         .unwrap_or((0, 0));
 
-    let mut remaining_seconds = use_signal(|| 25u64 * 60);
-    let mut initial_seconds = use_signal(|| 25u64 * 60);
-    let mut timer_state = use_signal(|| TimerState::Stopped);
+    let mut timer = timer;
     let mut is_hovered = use_signal(|| false);
-    let mut tick_count = use_signal(|| 0u64);
 
-    let _timer = use_resource(move || async move {
+    let _ticker = use_resource(move || async move {
         loop {
             #[cfg(target_arch = "wasm32")]
             gloo_timers::future::TimeoutFuture::new(1000).await;
@@ -51,19 +103,20 @@ pub fn StatusBar(
             #[cfg(not(target_arch = "wasm32"))]
             async_std::task::sleep(std::time::Duration::from_secs(1)).await;
 
-            if timer_state() == TimerState::Running && remaining_seconds() > 0 {
-                remaining_seconds.set(remaining_seconds() - 1);
-                tick_count.set(tick_count() + 1);
-                if remaining_seconds() == 0 {
-                    timer_state.set(TimerState::Stopped);
-                }
+            if timer.peek().state == TimerState::Running {
+                timer.write().tick();
             }
         }
     });
 
-    let hours = remaining_seconds() / 3600;
-    let minutes = (remaining_seconds() % 3600) / 60;
-    let seconds = remaining_seconds() % 60;
+    let Timer {
+        remaining_seconds,
+        initial_seconds,
+        state,
+    } = timer();
+    let hours = remaining_seconds / 3600;
+    let minutes = (remaining_seconds % 3600) / 60;
+    let seconds = remaining_seconds % 60;
 
     let time_display = if hours > 0 {
         format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
@@ -71,11 +124,11 @@ pub fn StatusBar(
         format!("{:02}:{:02}", minutes, seconds)
     };
 
-    let state = timer_state();
-    let is_finished = remaining_seconds() == 0;
+    let is_finished = remaining_seconds == 0;
 
     let sidebar_class = if is_sidebar_visible { "btn-bar active" } else { "btn-bar" };
     let preview_class = if is_preview_visible { "btn-bar active" } else { "btn-bar" };
+    let hybrid_class = if is_hybrid { "btn-bar active" } else { "btn-bar" };
     let focus_class = if is_focus_mode { "btn-bar active" } else { "btn-bar" };
 
     rsx! {
@@ -90,42 +143,59 @@ pub fn StatusBar(
                 div { class: "bar-section",
                     button {
                         class: sidebar_class,
-                        onclick: move |_| on_toggle_sidebar.call(()),
-                        title: "Notes (Ctrl+B)",
+                        onclick: move |_| on_command.call(Command::ToggleSidebar),
+                        title: keymap.tooltip(Command::ToggleSidebar),
                         "☰"
                     }
                     if has_note {
                         button {
                             class: preview_class,
-                            onclick: move |_| on_toggle_preview.call(()),
-                            title: "Preview (Ctrl+P)",
+                            onclick: move |_| on_command.call(Command::TogglePreview),
+                            title: keymap.tooltip(Command::TogglePreview),
                             "◫"
                         }
+                        button {
+                            class: hybrid_class,
+                            onclick: move |_| on_command.call(Command::ToggleHybrid),
+                            title: keymap.tooltip(Command::ToggleHybrid),
+                            "◩"
+                        }
                     }
                     button {
                         class: focus_class,
-                        onclick: move |_| on_toggle_focus.call(()),
-                        title: "Focus (Ctrl+Shift+F)",
+                        onclick: move |_| on_command.call(Command::ToggleFocusMode),
+                        title: keymap.tooltip(Command::ToggleFocusMode),
                         "⊙"
                     }
+                    button {
+                        class: "btn-bar",
+                        onclick: move |_| on_command.call(Command::ToggleCalendar),
+                        title: keymap.tooltip(Command::ToggleCalendar),
+                        "▦"
+                    }
                 }
 
                 // Center: Word count & timer
                 div { class: "bar-section bar-center",
+                    if let Some(status) = vim_status {
+                        button {
+                            class: "vim-status",
+                            onclick: move |_| on_command.call(Command::ToggleVimMode),
+                            title: "Turn off Vim keybindings",
+                            "{status}"
+                        }
+                    }
                     if word_count > 0 {
                         span { class: "word-count", "{word_count} words" }
                     }
 
                     div { class: "timer-group",
-                        for (duration, label) in PRESET_DURATIONS.iter() {
+                        for (command, label) in PRESET_COMMANDS.iter() {
                             button {
                                 class: "btn-preset",
-                                class: if initial_seconds() == *duration { "active" } else { "" },
-                                onclick: move |_| {
-                                    initial_seconds.set(*duration);
-                                    remaining_seconds.set(*duration);
-                                    timer_state.set(TimerState::Stopped);
-                                },
+                                class: if Some(initial_seconds) == command.timer_preset() { "active" } else { "" },
+                                onclick: move |_| on_command.call(*command),
+                                title: keymap.tooltip(*command),
                                 "{label}"
                             }
                         }
@@ -134,33 +204,11 @@ pub fn StatusBar(
                             class: if is_finished { "timer-finished" } else { "" },
                             "{time_display}"
                         }
-                        match state {
-                            TimerState::Stopped => rsx! {
-                                button {
-                                    class: "btn-timer",
-                                    onclick: move |_| {
-                                        if remaining_seconds() == 0 {
-                                            remaining_seconds.set(initial_seconds());
-                                        }
-                                        timer_state.set(TimerState::Running);
-                                    },
-                                    "▶"
-                                }
-                            },
-                            TimerState::Running => rsx! {
-                                button {
-                                    class: "btn-timer",
-                                    onclick: move |_| timer_state.set(TimerState::Paused),
-                                    "⏸"
-                                }
-                            },
-                            TimerState::Paused => rsx! {
-                                button {
-                                    class: "btn-timer",
-                                    onclick: move |_| timer_state.set(TimerState::Running),
-                                    "▶"
-                                }
-                            },
+                        button {
+                            class: "btn-timer",
+                            onclick: move |_| on_command.call(Command::StartPauseTimer),
+                            title: keymap.tooltip(Command::StartPauseTimer),
+                            if state == TimerState::Running { "⏸" } else { "▶" }
                         }
                     }
                 }
@@ -170,28 +218,32 @@ pub fn StatusBar(
                     if has_note {
                         button {
                             class: "btn-bar",
-                            onclick: move |_| {
-                                if font_size > 14 {
-                                    on_font_size_change.call(font_size - 2);
-                                }
-                            },
-                            title: "Smaller text",
+                            onclick: move |_| on_command.call(Command::DecreaseFontSize),
+                            title: keymap.tooltip(Command::DecreaseFontSize),
                             "A−"
                         }
                         button {
                             class: "btn-bar",
-                            onclick: move |_| {
-                                if font_size < 32 {
-                                    on_font_size_change.call(font_size + 2);
-                                }
-                            },
-                            title: "Larger text",
+                            onclick: move |_| on_command.call(Command::IncreaseFontSize),
+                            title: keymap.tooltip(Command::IncreaseFontSize),
                             "A+"
                         }
+                        button {
+                            class: if is_pinned { "btn-bar active" } else { "btn-bar" },
+                            onclick: move |_| on_command.call(Command::TogglePinned),
+                            title: if is_pinned { "Unpin note" } else { "Pin note" },
+                            "⚲"
+                        }
+                        button {
+                            class: if is_template { "btn-bar active" } else { "btn-bar" },
+                            onclick: move |_| on_command.call(Command::ToggleTemplate),
+                            title: if is_template { "Stop using as template" } else { "Use as template" },
+                            "⧉"
+                        }
                         button {
                             class: "btn-bar btn-delete",
-                            onclick: move |_| on_delete.call(()),
-                            title: "Delete note",
+                            onclick: move |_| on_command.call(Command::DeleteNote),
+                            title: keymap.tooltip(Command::DeleteNote),
                             "🗑"
                         }
                     }
@@ -0,0 +1,73 @@
+use crate::state::TabLabel;
+use dioxus::html::input_data::MouseButton;
+use dioxus::prelude::*;
+
+/// Open notes as tabs. Tabs reorder by dragging; middle-click closes one.
+#[component]
+pub fn TabBar(
+    tabs: Vec<TabLabel>,
+    active: usize,
+    on_select: EventHandler<usize>,
+    on_close: EventHandler<usize>,
+    // (from, to) indices of a dragged tab
+    on_move: EventHandler<(usize, usize)>,
+) -> Element {
+    let mut dragging = use_signal(|| None::<usize>);
+    let mut drop_target = use_signal(|| None::<usize>);
+
+    rsx! {
+        div { class: "tab-bar", role: "tablist",
+            for (i, tab) in tabs.into_iter().enumerate() {
+                div {
+                    key: "{i}-{tab.title}",
+                    class: "tab",
+                    class: if i == active { "active" } else { "" },
+                    class: if drop_target() == Some(i) && dragging() != Some(i) { "tab-drop-target" } else { "" },
+                    role: "tab",
+                    title: "{tab.title}",
+                    draggable: "true",
+                    onclick: move |_| on_select.call(i),
+                    onmousedown: move |evt: MouseEvent| {
+                        if evt.trigger_button() == Some(MouseButton::Auxiliary) {
+                            evt.prevent_default();
+                            on_close.call(i);
+                        }
+                    },
+                    ondragstart: move |_| dragging.set(Some(i)),
+                    ondragover: move |evt: DragEvent| {
+                        evt.prevent_default();
+                        drop_target.set(Some(i));
+                    },
+                    ondragleave: move |_| drop_target.set(None),
+                    ondrop: move |evt: DragEvent| {
+                        evt.prevent_default();
+                        if let Some(from) = dragging()
+                            && from != i
+                        {
+                            on_move.call((from, i));
+                        }
+                        dragging.set(None);
+                        drop_target.set(None);
+                    },
+                    ondragend: move |_| {
+                        dragging.set(None);
+                        drop_target.set(None);
+                    },
+                    span { class: "tab-title", "{tab.title}" }
+                    if tab.is_modified {
+                        span { class: "tab-modified", title: "Unsaved changes", "•" }
+                    }
+                    button {
+                        class: "tab-close",
+                        title: "Close tab",
+                        onclick: move |evt| {
+                            evt.stop_propagation();
+                            on_close.call(i);
+                        },
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::markdown::renderer::parser_options;
+use pulldown_cmark::{Event, Parser};
+use std::ops::Range;
+
+/// Byte ranges of the top-level blocks in `content` (paragraphs, headings,
+/// lists, quotes, code blocks, tables and rules), each covering whole lines
+/// without the final newline. Blank lines between blocks belong to none.
+pub fn split_blocks(content: &str) -> Vec<Range<usize>> {
+    let mut blocks: Vec<Range<usize>> = Vec::new();
+    let mut depth = 0usize;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        let is_top_level = match event {
+            Event::Start(_) => {
+                depth += 1;
+                depth == 1
+            }
+            Event::End(_) => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            Event::Rule => depth == 0,
+            _ => false,
+        };
+        if !is_top_level {
+            continue;
+        }
+
+        let start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let end = content[..range.end].trim_end_matches(['\n', '\r']).len().max(start);
+        let end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+        match blocks.last_mut() {
+            // Blocks sharing a line (like a lazy continuation) render together
+            Some(last) if start < last.end => last.end = last.end.max(end),
+            _ => blocks.push(start..end),
+        }
+    }
+
+    blocks
+}
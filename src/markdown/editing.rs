@@ -0,0 +1,490 @@
+use crate::markdown::TextEdit;
+
+/// Added by Tab. Four spaces nest under both `- ` and `1. ` items.
+const INDENT: &str = "    ";
+
+/// Characters that open a pair when typed; closers are skipped over.
+const PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('`', '`'),
+    ('*', '*'),
+    ('_', '_'),
+    ('~', '~'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Marker {
+    Bullet(char),
+    Task(char),
+    /// Number and the `.` or `)` after it
+    Ordered(u64, char),
+    Quote,
+}
+
+/// A line that starts a list item or blockquote.
+struct Item<'a> {
+    /// Leading whitespace
+    indent: &'a str,
+    marker: Marker,
+    /// The marker as written, with the space after it
+    prefix: &'a str,
+    /// Text after the marker
+    text: &'a str,
+}
+
+impl Item<'_> {
+    /// Marker for the item that follows this one.
+    fn next_prefix(&self) -> String {
+        match self.marker {
+            Marker::Bullet(c) => format!("{c} "),
+            Marker::Task(c) => format!("{c} [ ] "),
+            Marker::Ordered(n, delim) => format!("{}{delim} ", n + 1),
+            Marker::Quote => self.prefix.to_string(),
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        self.marker != Marker::Quote
+    }
+}
+
+fn parse_item(line: &str) -> Option<Item<'_>> {
+    let rest = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - rest.len()];
+
+    let (marker, marker_len) = if rest.starts_with('>') {
+        let len = rest.len() - rest.trim_start_matches(['>', ' ']).len();
+        (Marker::Quote, len)
+    } else if let Some(c) = rest.chars().next().filter(|c| matches!(c, '-' | '*' | '+')) {
+        if !rest[1..].starts_with(' ') {
+            return None;
+        }
+        let after = &rest[2..];
+        let is_task = ["[ ] ", "[x] ", "[X] "].iter().any(|b| after.starts_with(b))
+            || ["[ ]", "[x]", "[X]"].contains(&after);
+        if is_task {
+            (Marker::Task(c), (2 + 4).min(rest.len()))
+        } else {
+            (Marker::Bullet(c), 2)
+        }
+    } else {
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        // CommonMark allows up to nine digits
+        if digits == 0 || digits > 9 {
+            return None;
+        }
+        let delim = rest[digits..].chars().next().filter(|c| matches!(c, '.' | ')'))?;
+        if !rest[digits + 1..].starts_with(' ') {
+            return None;
+        }
+        let number = rest[..digits].parse().ok()?;
+        (Marker::Ordered(number, delim), digits + 2)
+    };
+
+    Some(Item {
+        indent,
+        marker,
+        prefix: &rest[..marker_len],
+        text: &rest[marker_len..],
+    })
+}
+
+fn line_bounds(content: &str, offset: usize) -> (usize, usize) {
+    let start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[offset..].find('\n').map_or(content.len(), |i| offset + i);
+    (start, end)
+}
+
+fn replace(content: &str, start: usize, end: usize, text: &str) -> TextEdit {
+    let caret = start + text.len();
+    TextEdit {
+        content: format!("{}{}{}", &content[..start], text, &content[end..]),
+        selection: (caret, caret),
+    }
+}
+
+/// Enter: continue the list item or blockquote the caret is in, or end the
+/// list when the item is empty. Other lines keep their indentation.
+pub fn insert_newline(content: &str, start: usize, end: usize) -> TextEdit {
+    let (line_start, line_end) = line_bounds(content, start);
+    let line = &content[line_start..line_end];
+
+    let Some(item) = parse_item(line) else {
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        // Only carry indentation when the caret is past it
+        let indent = if start - line_start >= indent.len() { indent } else { "" };
+        return replace(content, start, end, &format!("\n{indent}"));
+    };
+
+    let text_start = line_start + item.indent.len() + item.prefix.len();
+    if start != end || start < text_start {
+        return replace(content, start, end, "\n");
+    }
+
+    if item.text.trim().is_empty() {
+        // An empty nested item moves out a level; a top-level one ends the list
+        if !item.indent.is_empty() && item.is_list() {
+            let edit = outdent_line(content, line_start);
+            return renumber_list(&edit.content, edit.selection.0, edit.selection);
+        }
+        return replace(content, line_start, line_end, "");
+    }
+
+    let edit = replace(content, start, end, &format!("\n{}{}", item.indent, item.next_prefix()));
+    if matches!(item.marker, Marker::Ordered(..)) {
+        renumber_list(&edit.content, edit.selection.0, edit.selection)
+    } else {
+        edit
+    }
+}
+
+/// Tab and Shift+Tab. List items and multi-line selections are indented as
+/// whole lines; elsewhere Tab inserts indentation at the caret.
+pub fn indent(content: &str, start: usize, end: usize, outdent: bool) -> TextEdit {
+    let (first_start, first_end) = line_bounds(content, start);
+    let is_multiline = end > first_end;
+    if !outdent && !is_multiline && parse_item(&content[first_start..first_end]).is_none() {
+        return replace(content, start, end, INDENT);
+    }
+
+    // A selection ending at the start of a line leaves that line alone
+    let last = if is_multiline && content[..end].ends_with('\n') { end - 1 } else { end };
+    let mut result = String::with_capacity(content.len() + INDENT.len());
+    result.push_str(&content[..first_start]);
+    let (mut new_start, mut new_end) = (start, None);
+    let mut pos = first_start;
+    while pos <= last {
+        let (line_start, line_end) = line_bounds(content, pos);
+        let line = &content[line_start..line_end];
+        let new_line_start = result.len();
+        let new_line = if outdent {
+            outdented(line).to_string()
+        } else if line.trim().is_empty() {
+            line.to_string()
+        } else {
+            // An item moving into a new nested list starts it at 1
+            format!("{INDENT}{}", restart_number(line))
+        };
+        // Offsets inside removed indentation land at the start of the line
+        let moved = |offset: usize| {
+            let column = (offset - line_start) as isize + new_line.len() as isize - line.len() as isize;
+            new_line_start + column.max(0) as usize
+        };
+        if line_start <= start {
+            new_start = moved(start);
+        }
+        if (line_start..=line_end).contains(&end) {
+            new_end = Some(moved(end));
+        }
+        result.push_str(&new_line);
+        pos = line_end + 1;
+        if line_end < content.len() {
+            result.push('\n');
+        }
+    }
+    // Past the last changed line, the end moves with everything before it
+    let new_end = new_end.unwrap_or_else(|| end + result.len() - pos).max(new_start);
+    if pos <= content.len() {
+        result.push_str(&content[pos..]);
+    }
+
+    renumber_list(&result, new_start, (new_start, new_end))
+}
+
+fn outdent_line(content: &str, line_start: usize) -> TextEdit {
+    let (_, line_end) = line_bounds(content, line_start);
+    let line = &content[line_start..line_end];
+    replace(content, line_start, line_end, outdented(line))
+}
+
+fn outdented(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix('\t') {
+        return rest;
+    }
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    &line[spaces.min(INDENT.len())..]
+}
+
+fn restart_number(line: &str) -> String {
+    match parse_item(line) {
+        Some(Item {
+            indent,
+            marker: Marker::Ordered(_, delim),
+            text,
+            ..
+        }) => format!("{indent}1{delim} {text}"),
+        _ => line.to_string(),
+    }
+}
+
+/// Renumber the ordered lists in the list around byte `offset`, each nested
+/// list counting up from its first item. `selection` moves with its text.
+fn renumber_list(content: &str, offset: usize, selection: (usize, usize)) -> TextEdit {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let target = content[..offset].matches('\n').count();
+    let in_list = |line: &str| {
+        line.trim().is_empty()
+            || line.starts_with([' ', '\t'])
+            || parse_item(line).is_some_and(|item| item.is_list())
+    };
+    if !in_list(lines[target]) {
+        return TextEdit {
+            content: content.to_string(),
+            selection,
+        };
+    }
+    let mut first = target;
+    while first > 0 && in_list(lines[first - 1]) {
+        first -= 1;
+    }
+    let mut last = target;
+    while last + 1 < lines.len() && in_list(lines[last + 1]) {
+        last += 1;
+    }
+
+    // (indent, next number for ordered levels)
+    let mut levels: Vec<(usize, Option<u64>)> = Vec::new();
+    let mut new_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    for (i, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let Some(item) = parse_item(line).filter(|item| item.is_list()) else {
+            continue;
+        };
+        let indent = item.indent.len();
+        while levels.last().is_some_and(|&(level, _)| level > indent) {
+            levels.pop();
+        }
+        let Marker::Ordered(number, delim) = item.marker else {
+            match levels.last_mut() {
+                Some(level) if level.0 == indent => *level = (indent, None),
+                _ => levels.push((indent, None)),
+            }
+            continue;
+        };
+        let renumbered = match levels.last_mut() {
+            Some((level, Some(next))) if *level == indent => {
+                let n = *next;
+                *next += 1;
+                n
+            }
+            Some(level) if level.0 == indent => {
+                *level = (indent, Some(number + 1));
+                number
+            }
+            _ => {
+                levels.push((indent, Some(number + 1)));
+                number
+            }
+        };
+        if renumbered != number {
+            new_lines[i] = format!("{}{renumbered}{delim} {}", item.indent, item.text);
+        }
+    }
+
+    // Map each selection end through the changed lines
+    let map = |offset: usize| {
+        let line = content[..offset].matches('\n').count();
+        let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let column = offset - line_start;
+        let new_start: usize = new_lines[..line].iter().map(|l| l.len() + 1).sum();
+        let delta = new_lines[line].len() as isize - lines[line].len() as isize;
+        let indent = lines[line].len() - lines[line].trim_start_matches([' ', '\t']).len();
+        if delta != 0 && column > indent {
+            new_start + (column as isize + delta).max(indent as isize) as usize
+        } else {
+            new_start + column
+        }
+    };
+
+    TextEdit {
+        selection: (map(selection.0), map(selection.1)),
+        content: new_lines.join("\n"),
+    }
+}
+
+/// Whether typing `c` is handled by `insert_char`.
+pub fn is_pair_char(c: char) -> bool {
+    PAIRS.iter().any(|&(open, close)| c == open || c == close)
+}
+
+/// Type `c`, auto-pairing brackets, backticks and emphasis markers: a pair
+/// wraps the selection or is inserted around the caret, and typing a closer
+/// that is already there moves past it.
+pub fn insert_char(content: &str, start: usize, end: usize, c: char) -> TextEdit {
+    let typed = c.to_string();
+    let opener = PAIRS.iter().find(|&&(open, _)| open == c).map(|&(_, close)| close);
+    let is_symmetric = opener == Some(c);
+
+    if start != end {
+        return match opener {
+            Some(close) => TextEdit {
+                content: format!("{}{c}{}{close}{}", &content[..start], &content[start..end], &content[end..]),
+                selection: (start + c.len_utf8(), end + c.len_utf8()),
+            },
+            None => replace(content, start, end, &typed),
+        };
+    }
+
+    let before = &content[..start];
+    let after = &content[start..];
+    let run_before = before.chars().rev().take_while(|&ch| ch == c).count();
+    let run_after = after.chars().take_while(|&ch| ch == c).count();
+    // What precedes a run of markers decides whether they open or close
+    let preceding = before[..before.len() - run_before].chars().next_back();
+    let at_opening = preceding.is_none_or(|ch| ch.is_whitespace() || "([{".contains(ch));
+    let next = after.chars().next();
+    let at_closing = next.is_none_or(|ch| ch.is_whitespace() || ")]}.,;:!?".contains(ch));
+
+    // Typing into an empty `*|*` pair grows it to `**|**`
+    let grows = is_symmetric && run_before > 0 && run_before == run_after && at_opening;
+    if run_after > 0 && (opener.is_none() || is_symmetric) && !grows {
+        return TextEdit {
+            content: content.to_string(),
+            selection: (start + c.len_utf8(), start + c.len_utf8()),
+        };
+    }
+
+    let Some(close) = opener else {
+        return replace(content, start, end, &typed);
+    };
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    // `* ` and `- ` at the start of a line are list markers
+    let starts_line = before[line_start..].trim().is_empty();
+    let pairs = if is_symmetric {
+        grows || (run_before == 0 && at_opening && at_closing && !(c == '*' && starts_line))
+    } else {
+        at_closing
+    };
+    if !pairs {
+        return replace(content, start, end, &typed);
+    }
+
+    let caret = start + c.len_utf8();
+    TextEdit {
+        content: format!("{before}{c}{close}{after}"),
+        selection: (caret, caret),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text with `|` marking the caret, and the caret's offset.
+    fn caret(marked: &str) -> (String, usize) {
+        let offset = marked.find('|').expect("caret marker");
+        (marked.replacen('|', "", 1), offset)
+    }
+
+    /// Edited text with `|` at the caret.
+    fn marked(edit: TextEdit) -> String {
+        assert_eq!(edit.selection.0, edit.selection.1, "expected a caret");
+        let mut text = edit.content;
+        text.insert(edit.selection.0, '|');
+        text
+    }
+
+    fn enter(text: &str) -> String {
+        let (content, offset) = caret(text);
+        marked(insert_newline(&content, offset, offset))
+    }
+
+    fn tab(text: &str, outdent: bool) -> String {
+        let (content, offset) = caret(text);
+        marked(indent(&content, offset, offset, outdent))
+    }
+
+    fn typed(text: &str, c: char) -> String {
+        let (content, offset) = caret(text);
+        marked(insert_char(&content, offset, offset, c))
+    }
+
+    #[test]
+    fn enter_continues_lists_and_quotes() {
+        assert_eq!(enter("- one|"), "- one\n- |");
+        assert_eq!(enter("* one|"), "* one\n* |");
+        assert_eq!(enter("- [x] done|"), "- [x] done\n- [ ] |");
+        assert_eq!(enter("> quoted|"), "> quoted\n> |");
+        assert_eq!(enter("  - nested|"), "  - nested\n  - |");
+    }
+
+    #[test]
+    fn enter_renumbers_ordered_lists() {
+        assert_eq!(enter("1. a|\n2. b"), "1. a\n2. |\n3. b");
+        assert_eq!(enter("3) a|"), "3) a\n4) |");
+    }
+
+    #[test]
+    fn enter_on_empty_item_ends_or_outdents() {
+        assert_eq!(enter("- a\n- |"), "- a\n|");
+        assert_eq!(enter("- a\n    - |"), "- a\n- |");
+        assert_eq!(enter("> a\n> |"), "> a\n|");
+    }
+
+    #[test]
+    fn enter_elsewhere_keeps_indentation() {
+        assert_eq!(enter("    code|"), "    code\n    |");
+        assert_eq!(enter("  |text"), "  \n  |text");
+        assert_eq!(enter("|  text"), "\n|  text");
+        assert_eq!(enter("-|one"), "-\n|one");
+    }
+
+    #[test]
+    fn tab_indents_items_and_restarts_numbering() {
+        assert_eq!(tab("- a|", false), "    - a|");
+        assert_eq!(tab("1. a\n2. b|", false), "1. a\n    1. b|");
+        assert_eq!(tab("a|b", false), "a    |b");
+    }
+
+    #[test]
+    fn shift_tab_outdents() {
+        assert_eq!(tab("    - a|", true), "- a|");
+        assert_eq!(tab("\t- a|", true), "- a|");
+        assert_eq!(tab("1. a\n    1. b|\n2. c", true), "1. a\n2. b|\n3. c");
+    }
+
+    #[test]
+    fn tab_indents_every_selected_line() {
+        let content = "- a\n- b\n- c";
+        let edit = indent(content, 0, 7, false);
+        assert_eq!(edit.content, "    - a\n    - b\n- c");
+        assert_eq!(edit.selection, (4, 15));
+    }
+
+    #[test]
+    fn shift_tab_keeps_the_selection_end_on_its_line() {
+        let edit = indent("b日\n    }", 1, 6, true);
+        assert_eq!(edit.content, "b日\n}");
+        assert_eq!(edit.selection, (1, 5));
+        let edit = indent("    a\n    b", 0, 11, true);
+        assert_eq!(edit.content, "a\nb");
+        assert_eq!(edit.selection, (0, 3));
+    }
+
+    #[test]
+    fn brackets_pair_and_closers_skip() {
+        assert_eq!(typed("|", '('), "(|)");
+        assert_eq!(typed("a |", '['), "a [|]");
+        assert_eq!(typed("(|)", ')'), "()|");
+        assert_eq!(typed("|word", '('), "(|word");
+    }
+
+    #[test]
+    fn symmetric_markers_pair_only_between_words() {
+        assert_eq!(typed("a |", '`'), "a `|`");
+        assert_eq!(typed("a|b", '_'), "a_|b");
+        assert_eq!(typed("`|`", '`'), "``|``");
+        assert_eq!(typed("*a|*", '*'), "*a*|");
+        // A star starting a line is a list marker
+        assert_eq!(typed("|", '*'), "*|");
+    }
+
+    #[test]
+    fn pairs_wrap_the_selection() {
+        let edit = insert_char("say hi", 4, 6, '*');
+        assert_eq!(edit.content, "say *hi*");
+        assert_eq!(edit.selection, (5, 7));
+        assert!(is_pair_char('~') && !is_pair_char('a'));
+    }
+}
@@ -0,0 +1,183 @@
+/// A note with some byte ranges hidden, as the editor shows it while sections
+/// are folded. Offsets and edits in `text` map back to the note.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FoldedText {
+    pub text: String,
+    /// Hidden byte ranges of the note, sorted and disjoint
+    hidden: Vec<(usize, usize)>,
+}
+
+/// An edit to the note: bytes `start..end` replaced by `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl NoteEdit {
+    pub fn apply(&self, content: &str) -> String {
+        format!("{}{}{}", &content[..self.start], self.text, &content[self.end..])
+    }
+
+    /// Whether the edit reaches into bytes `start..end`, or removes the line
+    /// break right after them. Typing at `start` leaves them alone.
+    pub fn touches(&self, start: usize, end: usize) -> bool {
+        self.end > start && self.start <= end
+    }
+}
+
+impl FoldedText {
+    /// Hide byte ranges `hidden` of `content`. Overlapping ranges, as nested
+    /// folds give, are merged.
+    pub fn new(content: &str, mut hidden: Vec<(usize, usize)>) -> Self {
+        hidden.retain(|(start, end)| start < end && *end <= content.len());
+        hidden.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in hidden {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        let mut text = String::with_capacity(content.len());
+        let mut pos = 0;
+        for &(start, end) in &merged {
+            text.push_str(&content[pos..start]);
+            pos = end;
+        }
+        text.push_str(&content[pos..]);
+        Self { text, hidden: merged }
+    }
+
+    /// Positions in `text` where hidden ranges were taken out.
+    pub fn markers(&self) -> Vec<usize> {
+        let mut removed = 0;
+        self.hidden
+            .iter()
+            .map(|(start, end)| {
+                let marker = start - removed;
+                removed += end - start;
+                marker
+            })
+            .collect()
+    }
+
+    /// Byte offset in the note of byte `offset` of `text`. Where a range was
+    /// taken out, `after` picks the side of it.
+    pub fn to_note(&self, offset: usize, after: bool) -> usize {
+        let mut note = offset;
+        for (marker, (start, end)) in self.markers().into_iter().zip(&self.hidden) {
+            if marker < offset || (marker == offset && after) {
+                note += end - start;
+            } else {
+                break;
+            }
+        }
+        note
+    }
+
+    /// Byte offset in `text` of byte `offset` of the note; offsets inside a
+    /// hidden range land where it was taken out.
+    pub fn to_visible(&self, offset: usize) -> usize {
+        let mut removed = 0;
+        for &(start, end) in &self.hidden {
+            if offset <= start {
+                break;
+            }
+            removed += offset.min(end) - start;
+        }
+        offset - removed
+    }
+
+    /// The note edit that turns `text` into `visible`. Typing where a range
+    /// was taken out goes before it, deleting from there goes after it, and
+    /// replacing text on both sides takes the range with it.
+    pub fn edit_to(&self, visible: &str) -> NoteEdit {
+        let old = self.text.as_str();
+        let prefix = common_prefix(old, visible);
+        let suffix = common_suffix(&old[prefix..], &visible[prefix..]);
+        let (old_end, new_end) = (old.len() - suffix, visible.len() - suffix);
+        let start = self.to_note(prefix, old_end > prefix);
+        let end = self.to_note(old_end, false).max(start);
+        NoteEdit {
+            start,
+            end,
+            text: visible[prefix..new_end].to_string(),
+        }
+    }
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i)
+}
+
+fn common_suffix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .rev()
+        .zip(b.chars().rev())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, x), _)| a.len() - i - x.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "# A" hides "\nbody" and "# B" hides "\nmore"
+    const NOTE: &str = "# A\nbody\n# B\nmore";
+
+    fn folded() -> FoldedText {
+        FoldedText::new(NOTE, vec![(12, 17), (3, 8)])
+    }
+
+    #[test]
+    fn hides_ranges_and_marks_where_they_were() {
+        let folded = folded();
+        assert_eq!(folded.text, "# A\n# B");
+        assert_eq!(folded.markers(), vec![3, 7]);
+    }
+
+    #[test]
+    fn merges_overlapping_ranges_and_drops_bad_ones() {
+        let folded = FoldedText::new("abcdef", vec![(1, 3), (2, 4), (5, 5), (4, 99)]);
+        assert_eq!(folded.text, "aef");
+    }
+
+    #[test]
+    fn maps_offsets_both_ways() {
+        let folded = folded();
+        assert_eq!(folded.to_note(2, false), 2);
+        assert_eq!(folded.to_note(3, false), 3);
+        assert_eq!(folded.to_note(3, true), 8);
+        assert_eq!(folded.to_note(7, true), 17);
+        assert_eq!(folded.to_visible(9), 4);
+        // Inside a hidden range: where it was taken out
+        assert_eq!(folded.to_visible(5), 3);
+        assert_eq!(folded.to_visible(17), 7);
+    }
+
+    #[test]
+    fn edits_map_back_to_the_note() {
+        let folded = folded();
+        // Typing at a fold marker goes before the hidden text
+        let edit = folded.edit_to("# A!\n# B");
+        assert_eq!((edit.start, edit.end, edit.text.as_str()), (3, 3, "!"));
+        assert_eq!(edit.apply(NOTE), "# A!\nbody\n# B\nmore");
+        assert!(!edit.touches(3, 8));
+
+        // Deleting the line break after it takes the hidden text's end
+        let edit = folded.edit_to("# A# B");
+        assert_eq!((edit.start, edit.end), (8, 9));
+        assert!(edit.touches(3, 8));
+
+        // Replacing across a marker takes the hidden text with it; the one
+        // after the replaced text stays
+        let edit = folded.edit_to("# X");
+        assert_eq!(edit.apply(NOTE), "# X\nmore");
+    }
+}
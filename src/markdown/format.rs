@@ -0,0 +1,223 @@
+/// Inline Markdown that can be toggled on a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineFormat {
+    Bold,
+    Italic,
+    Code,
+    Strikethrough,
+    Link,
+}
+
+/// Text after an edit, with the selection to restore as byte offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub content: String,
+    pub selection: (usize, usize),
+}
+
+impl InlineFormat {
+    fn marker(self) -> &'static str {
+        match self {
+            InlineFormat::Bold => "**",
+            InlineFormat::Italic => "*",
+            InlineFormat::Code => "`",
+            InlineFormat::Strikethrough => "~~",
+            InlineFormat::Link => "",
+        }
+    }
+}
+
+/// Wrap or unwrap bytes `start..end` of `content` in `format`. An empty
+/// selection applies to the word at the cursor, or inserts an empty pair
+/// with the cursor between the markers.
+pub fn toggle_format(content: &str, start: usize, end: usize, format: InlineFormat) -> TextEdit {
+    let (start, end) = (start.min(end).min(content.len()), end.max(start).min(content.len()));
+    let cursor = (start == end).then_some(start);
+    let (start, end) = match cursor {
+        Some(cursor) => word_at(content, cursor),
+        // Emphasis doesn't render with spaces inside the markers
+        None => trim_range(content, start, end),
+    };
+
+    let edit = if format == InlineFormat::Link {
+        toggle_link(content, start, end)
+    } else {
+        toggle_marker(content, start, end, format)
+    };
+
+    // A caret stays a caret, moved along with the word it was in. Links
+    // keep their `url` placeholder selected instead.
+    match cursor {
+        Some(cursor) if start != end && format != InlineFormat::Link => {
+            let shift = edit.selection.0 as isize - start as isize;
+            let caret = (cursor as isize + shift).clamp(edit.selection.0 as isize, edit.selection.1 as isize);
+            TextEdit {
+                selection: (caret as usize, caret as usize),
+                ..edit
+            }
+        }
+        _ => edit,
+    }
+}
+
+fn toggle_marker(content: &str, start: usize, end: usize, format: InlineFormat) -> TextEdit {
+    let marker = format.marker();
+    let selected = &content[start..end];
+
+    // Markers inside the selection: `**bold**` selected whole
+    let inner = run_len(selected, marker, false).min(run_len(selected, marker, true));
+    if selected.len() > 2 * inner && has_format(inner, format) {
+        let m = marker.len();
+        let text = &selected[m..selected.len() - m];
+        return TextEdit {
+            content: format!("{}{}{}", &content[..start], text, &content[end..]),
+            selection: (start, start + text.len()),
+        };
+    }
+
+    // Markers around the selection: `bold` selected inside `**bold**`
+    let outer = run_len(&content[..start], marker, true).min(run_len(&content[end..], marker, false));
+    if has_format(outer, format) {
+        let m = marker.len();
+        return TextEdit {
+            content: format!("{}{}{}", &content[..start - m], selected, &content[end + m..]),
+            selection: (start - m, end - m),
+        };
+    }
+
+    TextEdit {
+        content: format!("{}{marker}{}{marker}{}", &content[..start], selected, &content[end..]),
+        selection: (start + marker.len(), end + marker.len()),
+    }
+}
+
+/// Whether a run of `run` marker characters on both sides means `format` is
+/// applied. Bold and italic share `*`: `***x***` is both, `**x**` only bold.
+fn has_format(run: usize, format: InlineFormat) -> bool {
+    match format {
+        InlineFormat::Italic => run == 1 || run >= 3,
+        InlineFormat::Bold | InlineFormat::Strikethrough => run >= 2,
+        InlineFormat::Code => run >= 1,
+        InlineFormat::Link => false,
+    }
+}
+
+/// Number of marker characters at the start (or end) of `text`.
+fn run_len(text: &str, marker: &str, from_end: bool) -> usize {
+    let Some(c) = marker.chars().next() else { return 0 };
+    if from_end {
+        text.chars().rev().take_while(|&ch| ch == c).count()
+    } else {
+        text.chars().take_while(|&ch| ch == c).count()
+    }
+}
+
+fn toggle_link(content: &str, start: usize, end: usize) -> TextEdit {
+    let selected = &content[start..end];
+
+    // Selection is the text of an existing `[text](url)`: unwrap it
+    if content[..start].ends_with('[')
+        && content[end..].starts_with("](")
+        && let Some(close) = content[end + 2..].find(')')
+    {
+        let after = end + 2 + close + 1;
+        return TextEdit {
+            content: format!("{}{}{}", &content[..start - 1], selected, &content[after..]),
+            selection: (start - 1, end - 1),
+        };
+    }
+
+    if selected.starts_with("http://") || selected.starts_with("https://") {
+        // Selected a URL: leave the caret where the text goes
+        let caret = start + 1;
+        return TextEdit {
+            content: format!("{}[]({}){}", &content[..start], selected, &content[end..]),
+            selection: (caret, caret),
+        };
+    }
+
+    // Select the `url` placeholder so typing replaces it
+    let url_start = end + 3;
+    TextEdit {
+        content: format!("{}[{}](url){}", &content[..start], selected, &content[end..]),
+        selection: if selected.is_empty() {
+            (start + 1, start + 1)
+        } else {
+            (url_start, url_start + 3)
+        },
+    }
+}
+
+/// Byte range of the word around `cursor`, or an empty range when the cursor
+/// isn't touching one.
+fn word_at(content: &str, cursor: usize) -> (usize, usize) {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let start = content[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(cursor, |(i, _)| i);
+    let end = content[cursor..]
+        .char_indices()
+        .find(|&(_, c)| !is_word(c))
+        .map_or(content.len(), |(i, _)| cursor + i);
+    (start, end)
+}
+
+fn trim_range(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let selected = &content[start..end];
+    let trimmed = selected.trim_start();
+    let start = start + (selected.len() - trimmed.len());
+    let end = start + trimmed.trim_end().len();
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toggled(content: &str, start: usize, end: usize, format: InlineFormat) -> (String, (usize, usize)) {
+        let edit = toggle_format(content, start, end, format);
+        (edit.content, edit.selection)
+    }
+
+    #[test]
+    fn wraps_the_trimmed_selection() {
+        assert_eq!(toggled("say hi", 4, 6, InlineFormat::Bold), ("say **hi**".into(), (6, 8)));
+        assert_eq!(toggled("say hi ", 3, 7, InlineFormat::Code), ("say `hi` ".into(), (5, 7)));
+        assert_eq!(toggled("a b", 0, 3, InlineFormat::Strikethrough), ("~~a b~~".into(), (2, 5)));
+    }
+
+    #[test]
+    fn unwraps_markers_around_or_inside_the_selection() {
+        assert_eq!(toggled("**hi**", 2, 4, InlineFormat::Bold), ("hi".into(), (0, 2)));
+        assert_eq!(toggled("**hi**", 0, 6, InlineFormat::Bold), ("hi".into(), (0, 2)));
+        assert_eq!(toggled("x `y` z", 3, 4, InlineFormat::Code), ("x y z".into(), (2, 3)));
+    }
+
+    #[test]
+    fn bold_and_italic_share_stars() {
+        assert_eq!(toggled("**hi**", 2, 4, InlineFormat::Italic), ("***hi***".into(), (3, 5)));
+        assert_eq!(toggled("***hi***", 3, 5, InlineFormat::Italic), ("**hi**".into(), (2, 4)));
+        assert_eq!(toggled("***hi***", 3, 5, InlineFormat::Bold), ("*hi*".into(), (1, 3)));
+    }
+
+    #[test]
+    fn caret_formats_the_word_it_is_in() {
+        assert_eq!(toggled("say hello", 6, 6, InlineFormat::Bold), ("say **hello**".into(), (8, 8)));
+        assert_eq!(toggled("say **hello**", 8, 8, InlineFormat::Bold), ("say hello".into(), (6, 6)));
+        assert_eq!(toggled("a  b", 2, 2, InlineFormat::Italic), ("a ** b".into(), (3, 3)));
+    }
+
+    #[test]
+    fn links_select_the_url_placeholder() {
+        assert_eq!(toggled("see docs", 4, 8, InlineFormat::Link), ("see [docs](url)".into(), (11, 14)));
+        assert_eq!(toggled("", 0, 0, InlineFormat::Link), ("[](url)".into(), (1, 1)));
+        assert_eq!(
+            toggled("https://x.y", 0, 11, InlineFormat::Link),
+            ("[](https://x.y)".into(), (1, 1))
+        );
+        assert_eq!(toggled("[docs](url)", 1, 5, InlineFormat::Link), ("docs".into(), (0, 4)));
+    }
+}
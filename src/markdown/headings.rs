@@ -0,0 +1,124 @@
+use crate::markdown::renderer::parser_options;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use std::ops::Range;
+
+/// A heading as the preview renders it, found by the same parser, so `#`
+/// lines in code blocks and `#tags` are left out and underlined (setext)
+/// headings are in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingInfo {
+    pub level: u8,
+    /// Plain text, with inline Markdown stripped
+    pub text: String,
+    /// Line the heading starts on
+    pub line: usize,
+    /// Bytes of the heading's lines, an underline included, without the
+    /// final line break
+    pub range: Range<usize>,
+    /// Written with `#` markers rather than underlined
+    pub is_atx: bool,
+}
+
+/// Headings at the top level of `content`, in order. Headings inside quotes
+/// and list items belong to those blocks, not to the note's structure.
+pub fn extract_headings(content: &str) -> Vec<HeadingInfo> {
+    let mut headings = Vec::new();
+    let mut depth = 0usize;
+    // Heading being read: level, start byte and text so far
+    let mut current: Option<(u8, usize, String)> = None;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) if depth == 0 => {
+                current = Some((level as u8, range.start, String::new()));
+                depth += 1;
+            }
+            Event::End(TagEnd::Heading(_)) if depth == 1 => {
+                depth = 0;
+                let Some((level, start, text)) = current.take() else { continue };
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.is_empty() {
+                    continue;
+                }
+                let end = content[..range.end].trim_end_matches(['\n', '\r']).len().max(start);
+                headings.push(HeadingInfo {
+                    level,
+                    text,
+                    line: content[..start].matches('\n').count(),
+                    range: start..end,
+                    is_atx: content[start..].trim_start().starts_with('#'),
+                });
+            }
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, heading)) = current.as_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some((_, _, heading)) = current.as_mut() {
+                    heading.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    headings
+}
+
+/// Text of the heading a note opens with, when only front matter and blank
+/// lines come before it.
+pub fn opening_heading(content: &str) -> Option<String> {
+    let first = extract_headings(content).into_iter().next()?;
+    let before = &content[..first.range.start];
+    let before = match before.strip_prefix("---") {
+        // Front matter runs to its closing `---` or `...` line
+        Some(rest) if rest.starts_with(['\n', '\r']) => rest
+            .split_inclusive('\n')
+            .skip_while(|line| !matches!(line.trim_end(), "---" | "..."))
+            .skip(1)
+            .collect::<String>(),
+        _ => before.to_string(),
+    };
+    before.trim().is_empty().then_some(first.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(content: &str) -> Vec<(u8, String, usize)> {
+        extract_headings(content)
+            .into_iter()
+            .map(|heading| (heading.level, heading.text, heading.line))
+            .collect()
+    }
+
+    #[test]
+    fn finds_atx_and_setext_headings() {
+        let content = "# One\n\ntext\n\nTwo\n---\n\n### *Three* `x`\n";
+        assert_eq!(
+            outline(content),
+            vec![(1, "One".into(), 0), (2, "Two".into(), 4), (3, "Three x".into(), 7)]
+        );
+        let headings = extract_headings(content);
+        assert!(headings[0].is_atx && !headings[1].is_atx);
+        assert_eq!(&content[headings[1].range.clone()], "Two\n---");
+    }
+
+    #[test]
+    fn skips_code_tags_quotes_and_empty_headings() {
+        let content = "```\n# not a heading\n```\n#tag\n> # quoted\n- # in a list\n#\n## Real\n";
+        assert_eq!(outline(content), vec![(2, "Real".into(), 7)]);
+    }
+
+    #[test]
+    fn opening_heading_allows_front_matter_before_it() {
+        assert_eq!(opening_heading("\n# Title\nbody"), Some("Title".into()));
+        assert_eq!(opening_heading("---\ntags: [a]\n---\n# Title\n"), Some("Title".into()));
+        assert_eq!(opening_heading("intro\n# Title\n"), None);
+        assert_eq!(opening_heading("no headings"), None);
+    }
+}
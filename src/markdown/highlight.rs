@@ -0,0 +1,503 @@
+/// A run of source text and the CSS classes that style it. Concatenating
+/// every span's `text` gives back the highlighted source exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub class: String,
+}
+
+/// Styles for a line of source: set by block syntax, applied to every span
+/// on the line.
+#[derive(Clone, Copy)]
+enum Block {
+    Paragraph,
+    Heading(usize),
+    Quote,
+    Code,
+}
+
+impl Block {
+    fn class(self) -> String {
+        match self {
+            Block::Paragraph => String::new(),
+            Block::Heading(level) => format!("md-heading md-h{level}"),
+            Block::Quote => String::from("md-quote"),
+            Block::Code => String::from("md-code-block"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Spans {
+    spans: Vec<Span>,
+}
+
+impl Spans {
+    fn push(&mut self, text: &str, block: Block, class: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let class = match (block.class(), class) {
+            (block, "") => block,
+            (block, class) if block.is_empty() => class.to_string(),
+            (block, class) => format!("{block} {class}"),
+        };
+        // Merge with the previous span when nothing changes
+        if let Some(last) = self.spans.last_mut().filter(|last| last.class == class) {
+            last.text.push_str(text);
+        } else {
+            self.spans.push(Span {
+                text: text.to_string(),
+                class,
+            });
+        }
+    }
+}
+
+/// Split Markdown source into styled spans for the editor's highlight layer.
+pub fn highlight(content: &str) -> Vec<Span> {
+    let mut out = Spans::default();
+    let lines: Vec<&str> = content.split('\n').collect();
+    // Open fence and the language after it
+    let mut fence: Option<(String, String)> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let newline = if i + 1 < lines.len() { "\n" } else { "" };
+
+        if let Some((marker, language)) = &fence {
+            if line.trim_start().starts_with(marker.as_str())
+                && line.trim().chars().all(|c| c == marker.as_bytes()[0] as char)
+            {
+                out.push(line, Block::Code, "md-marker");
+                fence = None;
+            } else {
+                highlight_code(&mut out, line, language);
+            }
+            out.push(newline, Block::Code, "");
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].iter().find(|m| trimmed.starts_with(**m)) {
+            let run = trimmed
+                .chars()
+                .take_while(|&c| c == marker.as_bytes()[0] as char)
+                .count();
+            let language = trimmed[run..].split_whitespace().next().unwrap_or("");
+            out.push(line, Block::Code, "md-marker");
+            out.push(newline, Block::Code, "");
+            fence = Some((trimmed[..run].to_string(), language.to_lowercase()));
+            continue;
+        }
+
+        highlight_line(&mut out, line, lines.get(i + 1).copied());
+        out.push(newline, Block::Paragraph, "");
+    }
+
+    out.spans
+}
+
+fn highlight_line(out: &mut Spans, line: &str, next: Option<&str>) {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    // ATX heading: `## Title`
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && (trimmed.len() == hashes || trimmed[hashes..].starts_with(' ')) {
+        let block = Block::Heading(hashes);
+        let marker_len = indent.len() + hashes + usize::from(trimmed.len() > hashes);
+        out.push(&line[..marker_len], block, "md-marker");
+        highlight_inline(out, &line[marker_len..], block);
+        return;
+    }
+
+    if is_rule(trimmed) {
+        out.push(line, Block::Paragraph, "md-marker");
+        return;
+    }
+
+    // Setext underline
+    if trimmed.chars().all(|c| c == '=') && !trimmed.is_empty() {
+        out.push(line, Block::Paragraph, "md-marker");
+        return;
+    }
+
+    // Setext heading: a paragraph line underlined by `===` or `---`
+    let is_paragraph = !trimmed.is_empty() && !trimmed.starts_with(['>', '|']);
+    if let Some(next) = next.map(str::trim).filter(|n| !n.is_empty() && is_paragraph)
+        && (next.chars().all(|c| c == '=') || (next.chars().all(|c| c == '-') && !is_list_item(trimmed)))
+    {
+        let level = if next.starts_with('=') { 1 } else { 2 };
+        highlight_inline(out, line, Block::Heading(level));
+        return;
+    }
+
+    if trimmed.starts_with('>') {
+        let prefix = trimmed.len() - trimmed.trim_start_matches(['>', ' ']).len();
+        let marker_len = indent.len() + prefix;
+        out.push(&line[..marker_len], Block::Quote, "md-marker");
+        highlight_inline(out, &line[marker_len..], Block::Quote);
+        return;
+    }
+
+    if let Some(marker_len) = list_marker_len(trimmed) {
+        out.push(indent, Block::Paragraph, "");
+        out.push(&trimmed[..marker_len], Block::Paragraph, "md-list-marker");
+        let rest = &trimmed[marker_len..];
+        let task = ["[ ]", "[x]", "[X]"].iter().find(|b| rest.starts_with(**b));
+        if let Some(task) = task {
+            out.push(task, Block::Paragraph, "md-task");
+            let done = task != &"[ ]";
+            let text = &rest[task.len()..];
+            if done {
+                out.push(text, Block::Paragraph, "md-task-done");
+            } else {
+                highlight_inline(out, text, Block::Paragraph);
+            }
+        } else {
+            highlight_inline(out, rest, Block::Paragraph);
+        }
+        return;
+    }
+
+    if trimmed.starts_with('|') {
+        highlight_table_row(out, line);
+        return;
+    }
+
+    highlight_inline(out, line, Block::Paragraph);
+}
+
+fn is_rule(trimmed: &str) -> bool {
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ['-', '*', '_'].iter().any(|&m| compact.chars().all(|c| c == m))
+}
+
+fn is_list_item(trimmed: &str) -> bool {
+    list_marker_len(trimmed).is_some()
+}
+
+/// Length of a `- `, `* `, `+ ` or `1. ` marker at the start of `trimmed`.
+fn list_marker_len(trimmed: &str) -> Option<usize> {
+    if trimmed.starts_with(['-', '*', '+']) && trimmed[1..].starts_with(' ') {
+        return Some(2);
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if (1..=9).contains(&digits) && trimmed[digits..].starts_with(['.', ')']) && trimmed[digits + 1..].starts_with(' ')
+    {
+        return Some(digits + 2);
+    }
+    None
+}
+
+fn highlight_table_row(out: &mut Spans, line: &str) {
+    let is_separator = line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '));
+    if is_separator {
+        out.push(line, Block::Paragraph, "md-marker");
+        return;
+    }
+    for (i, cell) in line.split('|').enumerate() {
+        if i > 0 {
+            out.push("|", Block::Paragraph, "md-marker");
+        }
+        highlight_inline(out, cell, Block::Paragraph);
+    }
+}
+
+/// Emphasis, code spans, links, wiki links, tags and bare URLs.
+fn highlight_inline(out: &mut Spans, text: &str, block: Block) {
+    let mut bold = false;
+    let mut italic = false;
+    let mut strike = false;
+    let mut plain_start = 0;
+    let mut i = 0;
+
+    let style = |bold: bool, italic: bool, strike: bool| {
+        let mut classes = Vec::new();
+        if bold {
+            classes.push("md-bold");
+        }
+        if italic {
+            classes.push("md-italic");
+        }
+        if strike {
+            classes.push("md-strike");
+        }
+        classes.join(" ")
+    };
+
+    while i < text.len() {
+        let rest = &text[i..];
+        let prev = text[..i].chars().next_back();
+        let flush = |out: &mut Spans, end: usize| {
+            out.push(&text[plain_start..end], block, &style(bold, italic, strike));
+        };
+
+        // `code`
+        if rest.starts_with('`') {
+            let run = rest.chars().take_while(|&c| c == '`').count();
+            let ticks = &rest[..run];
+            if let Some(close) = rest[run..].find(ticks) {
+                flush(out, i);
+                out.push(ticks, block, "md-marker");
+                out.push(&rest[run..run + close], block, "md-code");
+                out.push(ticks, block, "md-marker");
+                i += run + close + run;
+                plain_start = i;
+                continue;
+            }
+        }
+
+        // [[Wiki link]]
+        if rest.starts_with("[[")
+            && let Some(close) = rest.find("]]")
+        {
+            flush(out, i);
+            out.push("[[", block, "md-marker");
+            out.push(&rest[2..close], block, "md-link");
+            out.push("]]", block, "md-marker");
+            i += close + 2;
+            plain_start = i;
+            continue;
+        }
+
+        // [text](url) and ![alt](url)
+        if rest.starts_with('[') || rest.starts_with("![") {
+            let open = if rest.starts_with('!') { 2 } else { 1 };
+            if let Some(mid) = rest.find("](").filter(|&mid| !rest[open..mid].contains(']'))
+                && let Some(close) = rest[mid + 2..].find(')')
+            {
+                let end = mid + 2 + close;
+                flush(out, i);
+                out.push(&rest[..open], block, "md-marker");
+                out.push(&rest[open..mid], block, "md-link");
+                out.push("](", block, "md-marker");
+                out.push(&rest[mid + 2..end], block, "md-url");
+                out.push(")", block, "md-marker");
+                i += end + 1;
+                plain_start = i;
+                continue;
+            }
+        }
+
+        // Bare URL
+        if (rest.starts_with("https://") || rest.starts_with("http://"))
+            && prev.is_none_or(|c| c.is_whitespace() || c == '(' || c == '<')
+        {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(rest.len());
+            flush(out, i);
+            out.push(&rest[..end], block, "md-url");
+            i += end;
+            plain_start = i;
+            continue;
+        }
+
+        // #tag
+        if rest.starts_with('#') && prev.is_none_or(char::is_whitespace) {
+            let end = 1 + rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+                .unwrap_or(rest.len() - 1);
+            if rest[1..end].chars().any(char::is_alphabetic) {
+                flush(out, i);
+                out.push(&rest[..end], block, "md-tag");
+                i += end;
+                plain_start = i;
+                continue;
+            }
+        }
+
+        // Emphasis and strikethrough delimiters
+        let delimiter = ["***", "___", "**", "__", "~~", "*", "_"]
+            .iter()
+            .find(|d| rest.starts_with(**d));
+        if let Some(&delimiter) = delimiter {
+            let after = rest[delimiter.len()..].chars().next();
+            let closes = |open: bool| open && prev.is_some_and(|c| !c.is_whitespace());
+            // Underscores don't emphasize inside words
+            let intraword = delimiter.starts_with('_')
+                && prev.is_some_and(char::is_alphanumeric)
+                && after.is_some_and(char::is_alphanumeric);
+            let opens =
+                after.is_some_and(|c| !c.is_whitespace()) && !intraword && rest[delimiter.len()..].contains(delimiter);
+
+            let (is_bold, is_italic, is_strike) = match delimiter {
+                "***" | "___" => (true, true, false),
+                "**" | "__" => (true, false, false),
+                "~~" => (false, false, true),
+                _ => (false, true, false),
+            };
+            let open = (!is_bold || bold) && (!is_italic || italic) && (!is_strike || strike);
+            if closes(open) || opens {
+                flush(out, i);
+                out.push(delimiter, block, "md-marker");
+                bold ^= is_bold;
+                italic ^= is_italic;
+                strike ^= is_strike;
+                i += delimiter.len();
+                plain_start = i;
+                continue;
+            }
+        }
+
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+
+    out.push(&text[plain_start..], block, &style(bold, italic, strike));
+}
+
+/// Keywords for the languages fenced code blocks are most often tagged with.
+fn keywords(language: &str) -> &'static [&'static str] {
+    match language {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false", "fn", "for", "if",
+            "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+            "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        "js" | "javascript" | "ts" | "typescript" | "jsx" | "tsx" => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "typeof",
+            "undefined",
+            "var",
+            "void",
+            "while",
+            "yield",
+        ],
+        "py" | "python" => &[
+            "and", "as", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+            "False", "finally", "for", "from", "if", "import", "in", "is", "lambda", "None", "not", "or", "pass",
+            "raise", "return", "True", "try", "while", "with", "yield",
+        ],
+        "go" => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "false",
+            "for",
+            "func",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "nil",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "true",
+            "type",
+            "var",
+        ],
+        "sh" | "bash" | "shell" | "zsh" => &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local",
+            "return", "then", "while",
+        ],
+        "json" | "yaml" | "yml" | "toml" => &["true", "false", "null"],
+        _ => &[],
+    }
+}
+
+fn line_comment(language: &str) -> Option<&'static str> {
+    match language {
+        "rust" | "rs" | "js" | "javascript" | "ts" | "typescript" | "jsx" | "tsx" | "go" | "c" | "cpp" | "java"
+        | "swift" | "kotlin" => Some("//"),
+        "py" | "python" | "sh" | "bash" | "shell" | "zsh" | "yaml" | "yml" | "toml" | "ruby" | "rb" => Some("#"),
+        "sql" | "lua" => Some("--"),
+        _ => None,
+    }
+}
+
+/// Keywords, strings, numbers and line comments inside a fenced code block.
+fn highlight_code(out: &mut Spans, line: &str, language: &str) {
+    let keywords = keywords(language);
+    let comment = line_comment(language);
+    if keywords.is_empty() && comment.is_none() {
+        out.push(line, Block::Code, "");
+        return;
+    }
+
+    let mut i = 0;
+    let mut plain_start = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        let c = rest.chars().next().unwrap_or(' ');
+
+        let token = if comment.is_some_and(|comment| rest.starts_with(comment)) {
+            Some((rest.len(), "md-token-comment"))
+        } else if c == '"' || c == '\'' || c == '`' {
+            let end = rest[1..].find(c).map_or(rest.len(), |end| end + 2);
+            Some((end, "md-token-string"))
+        } else if c.is_ascii_digit() && !line[..i].ends_with(|p: char| p.is_alphanumeric() || p == '_') {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .unwrap_or(rest.len());
+            Some((end, "md-token-number"))
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let is_keyword =
+                keywords.contains(&&rest[..end]) && !line[..i].ends_with(|p: char| p.is_alphanumeric() || p == '_');
+            if is_keyword {
+                Some((end, "md-token-keyword"))
+            } else {
+                // Skip the whole word so keywords inside it don't match
+                i += end;
+                continue;
+            }
+        } else {
+            None
+        };
+
+        match token {
+            Some((len, class)) => {
+                out.push(&line[plain_start..i], Block::Code, "");
+                out.push(&rest[..len], Block::Code, class);
+                i += len;
+                plain_start = i;
+            }
+            None => i += c.len_utf8(),
+        }
+    }
+    out.push(&line[plain_start..], Block::Code, "");
+}
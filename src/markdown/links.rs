@@ -0,0 +1,72 @@
+/// Where a link inside a note points to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// `[[Title]]`, `[[Title|label]]` or `[[Title#Heading]]`
+    Title(String),
+    /// `[label](note-id)`
+    Id(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteLink {
+    pub target: LinkTarget,
+    pub line: usize,
+}
+
+impl LinkTarget {
+    /// Whether this link points at the note with the given id and title.
+    pub fn points_to(&self, id: &str, title: &str) -> bool {
+        match self {
+            LinkTarget::Title(t) => t.to_lowercase() == title.to_lowercase(),
+            LinkTarget::Id(i) => i == id,
+        }
+    }
+}
+
+pub fn extract_links(content: &str) -> Vec<NoteLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        // Wiki links: [[Title]], [[Title|label]], [[Title#Heading]]
+        let mut rest = line;
+        while let Some(start) = rest.find("[[") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("]]") else { break };
+            let target = after[..end].split(['|', '#']).next().unwrap_or("").trim();
+            if !target.is_empty() {
+                links.push(NoteLink {
+                    target: LinkTarget::Title(target.to_string()),
+                    line: line_num,
+                });
+            }
+            rest = &after[end + 2..];
+        }
+
+        // Markdown links whose destination is a note id: [label](note-id)
+        let mut rest = line;
+        while let Some(start) = rest.find("](") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find(')') else { break };
+            let dest = after[..end].trim();
+            if !dest.is_empty() && !dest.contains("://") && !dest.starts_with('#') {
+                links.push(NoteLink {
+                    target: LinkTarget::Id(dest.to_string()),
+                    line: line_num,
+                });
+            }
+            rest = &after[end + 1..];
+        }
+    }
+
+    links
+}
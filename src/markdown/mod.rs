@@ -1,3 +1,27 @@
+mod blocks;
+mod editing;
+mod folding;
+mod format;
+mod headings;
+mod highlight;
+mod links;
 mod renderer;
+mod sections;
+mod tags;
+mod tasks;
+mod title;
 
+pub use blocks::split_blocks;
+pub use editing::{indent, insert_char, insert_newline, is_pair_char};
+pub use folding::{FoldedText, NoteEdit};
+pub use format::{toggle_format, InlineFormat, TextEdit};
+pub use headings::{extract_headings, opening_heading, HeadingInfo};
+pub use highlight::highlight;
+pub use links::{extract_links, LinkTarget};
 pub use renderer::render_markdown;
+pub use sections::{
+    fold_range, move_section, section_at, section_end, section_word_counts, shift_section,
+};
+pub use tags::extract_tags;
+pub use tasks::{extract_tasks, toggle_task};
+pub use title::{first_text_line, front_matter_title};
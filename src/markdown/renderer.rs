@@ -1,12 +1,17 @@
 use pulldown_cmark::{html, Options, Parser};
 
-pub fn render_markdown(input: &str) -> String {
+pub(super) fn parser_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_TASKLISTS);
+    // Keep front matter (e.g. `title:`) out of the rendered preview
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    options
+}
 
-    let parser = Parser::new_ext(input, options);
+pub fn render_markdown(input: &str) -> String {
+    let parser = Parser::new_ext(input, parser_options());
     let mut html_output = String::new();
     html::push_html(&mut html_output, parser);
     html_output
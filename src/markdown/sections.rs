@@ -0,0 +1,156 @@
+use crate::markdown::HeadingInfo;
+
+/// Line after the section under `headings[index]`: the next heading at the
+/// same or a higher level, or `line_count` at the end of the note.
+pub fn section_end(headings: &[HeadingInfo], index: usize, line_count: usize) -> usize {
+    let level = headings[index].level;
+    headings[index + 1..]
+        .iter()
+        .find(|heading| heading.level <= level)
+        .map_or(line_count, |heading| heading.line)
+}
+
+/// Index of the heading whose section `line` is in, innermost first.
+pub fn section_at(headings: &[HeadingInfo], line: usize) -> Option<usize> {
+    headings.iter().rposition(|heading| heading.line <= line)
+}
+
+/// Words under each heading, subsections included but headings left out.
+pub fn section_word_counts(content: &str, headings: &[HeadingInfo]) -> Vec<usize> {
+    let mut line_words: Vec<usize> = content.split('\n').map(|line| line.split_whitespace().count()).collect();
+    for heading in headings {
+        // Underlined headings span two lines or more
+        let lines = content[heading.range.clone()].matches('\n').count();
+        line_words[heading.line..=heading.line + lines].fill(0);
+    }
+    (0..headings.len())
+        .map(|i| {
+            let end = section_end(headings, i, line_words.len());
+            line_words[(headings[i].line + 1).min(end)..end].iter().sum()
+        })
+        .collect()
+}
+
+/// Bytes of `content` that folding `headings[index]` hides: from the end of
+/// the heading, underline included, to the end of the section's last line,
+/// so the line break before the next section stays. `None` when the section
+/// has no body.
+pub fn fold_range(content: &str, headings: &[HeadingInfo], index: usize) -> Option<(usize, usize)> {
+    let mut line_ends = Vec::new();
+    let mut pos = 0;
+    for line in content.split('\n') {
+        pos += line.len();
+        line_ends.push(pos);
+        pos += 1;
+    }
+    let end_line = section_end(headings, index, line_ends.len());
+    let start = headings[index].range.end;
+    let end = line_ends[end_line.checked_sub(1)?];
+    (end > start).then_some((start, end))
+}
+
+/// `content` with the section under `headings[from]` moved to start at line
+/// `to`: another heading's line, or the line count for the end of the note.
+/// `None` when `to` is inside the section, which leaves nothing to move.
+pub fn move_section(content: &str, headings: &[HeadingInfo], from: usize, to: usize) -> Option<String> {
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    // A final line break stays at the end of the note
+    let body_lines = lines.len() - usize::from(lines.len() > 1 && lines.last() == Some(&""));
+    let start = headings[from].line;
+    let end = section_end(headings, from, lines.len()).min(body_lines);
+    let to = to.min(body_lines);
+    if (start..=end).contains(&to) {
+        return None;
+    }
+
+    let section: Vec<&str> = lines.drain(start..end).collect();
+    let at = if to > start { to - section.len() } else { to };
+    lines.splice(at..at, section);
+    Some(lines.join("\n"))
+}
+
+/// `content` with `headings[index]` and every heading in its section moved
+/// `delta` levels, negative to promote. `None` when one would leave levels
+/// 1 to 6, so the section keeps its shape. Underlined headings past level 2
+/// become `#` headings.
+pub fn shift_section(content: &str, headings: &[HeadingInfo], index: usize, delta: i8) -> Option<String> {
+    let end = section_end(headings, index, content.split('\n').count());
+    let section: Vec<(&HeadingInfo, u8)> = headings[index..]
+        .iter()
+        .take_while(|heading| heading.line < end)
+        .map(|heading| {
+            let level = heading.level.checked_add_signed(delta).filter(|level| (1..=6).contains(level))?;
+            Some((heading, level))
+        })
+        .collect::<Option<_>>()?;
+
+    // From the end, so the byte ranges of earlier headings stay put
+    let mut shifted = content.to_string();
+    for (heading, level) in section.into_iter().rev() {
+        let line_start = content[..heading.range.start].rfind('\n').map_or(0, |i| i + 1);
+        if heading.is_atx {
+            let line = &content[line_start..heading.range.end];
+            let marker_start = line_start + line.len() - line.trim_start().len();
+            let marker_end = marker_start + heading.level as usize;
+            shifted.replace_range(marker_start..marker_end, &"#".repeat(level as usize));
+            continue;
+        }
+        let underline_start = content[..heading.range.end].rfind('\n').map_or(0, |i| i + 1);
+        let underline = content[underline_start..heading.range.end].trim();
+        let replacement = match level {
+            1 => "=".repeat(underline.len()),
+            2 => "-".repeat(underline.len()),
+            _ => {
+                let text: Vec<&str> = content[line_start..underline_start].lines().map(str::trim).collect();
+                shifted.replace_range(
+                    line_start..heading.range.end,
+                    &format!("{} {}", "#".repeat(level as usize), text.join(" ")),
+                );
+                continue;
+            }
+        };
+        shifted.replace_range(underline_start..heading.range.end, &replacement);
+    }
+    Some(shifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::extract_headings;
+
+    fn moved(content: &str, from: usize, to: usize) -> Option<String> {
+        move_section(content, &extract_headings(content), from, to)
+    }
+
+    fn shifted(content: &str, index: usize, delta: i8) -> Option<String> {
+        shift_section(content, &extract_headings(content), index, delta)
+    }
+
+    #[test]
+    fn moves_a_section_with_its_subsections() {
+        let note = "# A\na\n## A1\n# B\nb\n";
+        assert_eq!(moved(note, 0, 3).as_deref(), None);
+        assert_eq!(moved(note, 0, 5).as_deref(), Some("# B\nb\n# A\na\n## A1\n"));
+        assert_eq!(moved(note, 2, 0).as_deref(), Some("# B\nb\n# A\na\n## A1\n"));
+    }
+
+    #[test]
+    fn moving_inside_the_section_does_nothing() {
+        assert_eq!(moved("# A\n## A1\n# B", 0, 1), None);
+    }
+
+    #[test]
+    fn shifts_every_heading_in_the_section() {
+        let note = "# A\n## A1\n# B";
+        assert_eq!(shifted(note, 0, 1).as_deref(), Some("## A\n### A1\n# B"));
+        assert_eq!(shifted(note, 1, -1).as_deref(), Some("# A\n# A1\n# B"));
+        assert_eq!(shifted(note, 0, -1), None);
+    }
+
+    #[test]
+    fn underlined_headings_become_atx_past_level_two() {
+        assert_eq!(shifted("A\n===\nx", 0, 1).as_deref(), Some("A\n---\nx"));
+        assert_eq!(shifted("A\n---\nx", 0, 1).as_deref(), Some("### A\nx"));
+    }
+}
@@ -0,0 +1,48 @@
+/// Collect `#tags` from note content, lowercased and deduplicated in order of
+/// first appearance. Headings, code spans and fenced code blocks are skipped.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut prev: Option<char> = None;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && prev.is_none_or(char::is_whitespace) {
+                let mut tag = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !is_tag_char(next) {
+                        break;
+                    }
+                    tag.push(next);
+                    chars.next();
+                }
+                let tag = tag.trim_end_matches('/').to_lowercase();
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                prev = Some('#');
+                continue;
+            }
+            prev = Some(c);
+        }
+    }
+
+    tags
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '/')
+}
@@ -0,0 +1,80 @@
+use crate::markdown::renderer::parser_options;
+use pulldown_cmark::{Event, Parser};
+use std::ops::Range;
+
+/// A `- [ ]` or `- [x]` list item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskItem {
+    pub line: usize,
+    pub done: bool,
+    pub text: String,
+}
+
+/// Checkboxes as the preview renders them, so task-like lines in code
+/// blocks are left out: whether each is ticked, and its `[ ]` bytes.
+fn checkboxes(content: &str) -> impl Iterator<Item = (bool, Range<usize>)> + '_ {
+    Parser::new_ext(content, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(done) => Some((done, range)),
+            _ => None,
+        })
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count()
+}
+
+pub fn extract_tasks(content: &str) -> Vec<TaskItem> {
+    checkboxes(content)
+        .map(|(done, range)| {
+            let line_end = content[range.end..].find('\n').map_or(content.len(), |i| range.end + i);
+            TaskItem {
+                line: line_of(content, range.start),
+                done,
+                text: content[range.end..line_end].trim().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// `content` with the checkbox of the task on `line` ticked or cleared.
+pub fn toggle_task(content: &str, line: usize) -> String {
+    let mut toggled = content.to_string();
+    if let Some((done, range)) = checkboxes(content).find(|(_, range)| line_of(content, range.start) == line) {
+        toggled.replace_range(range, if done { "[ ]" } else { "[x]" });
+    }
+    toggled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tasks_outside_code() {
+        let tasks = extract_tasks("- [ ] open\n1. [X] done\n\n```\n- [ ] code\n```\n- [] not a task");
+        assert_eq!(
+            tasks,
+            vec![
+                TaskItem {
+                    line: 0,
+                    done: false,
+                    text: "open".into(),
+                },
+                TaskItem {
+                    line: 1,
+                    done: true,
+                    text: "done".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn toggles_the_task_on_a_line() {
+        assert_eq!(toggle_task("- [ ] a\n  - [x] b", 0), "- [x] a\n  - [x] b");
+        assert_eq!(toggle_task("- [ ] a\n  - [x] b", 1), "- [ ] a\n  - [ ] b");
+        assert_eq!(toggle_task("```\n- [ ] a\n```", 1), "```\n- [ ] a\n```");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::markdown::renderer::parser_options;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+/// The `title:` value of a YAML front matter block at the top of `content`.
+pub fn front_matter_title(content: &str) -> Option<String> {
+    let mut lines = content.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    for line in lines {
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("title:") {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'').trim();
+            if !value.is_empty() {
+                return Some(value.to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Plain text of the first line of the first block that has any, with
+/// Markdown syntax (emphasis, links, inline code) stripped. Front matter,
+/// code blocks and image alt text are skipped.
+pub fn first_text_line(content: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut skip_depth = 0usize;
+
+    for event in Parser::new_ext(content, parser_options()) {
+        match event {
+            Event::Start(Tag::MetadataBlock(_) | Tag::CodeBlock(_) | Tag::Image { .. }) => {
+                skip_depth += 1;
+            }
+            Event::End(TagEnd::MetadataBlock(_) | TagEnd::CodeBlock | TagEnd::Image) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Event::Text(t) | Event::Code(t) if skip_depth == 0 => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak if !text.trim().is_empty() => break,
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::TableCell | TagEnd::Item)
+                if !text.trim().is_empty() =>
+            {
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
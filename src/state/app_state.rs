@@ -1,113 +1,888 @@
+use crate::state::backlinks::{find_backlinks, Backlink};
+use crate::state::daily::{daily_content, daily_date, find_daily_note};
+use crate::state::graph::NoteGraph;
+use crate::state::keymap::Keymap;
 use crate::state::note::{Note, NoteSummary, SaveStatus};
+use crate::state::query::{parse_query, QueryError};
+use crate::state::replace::{apply_matches, find_matches, NoteMatches, ReplaceQuery};
+use crate::state::search::{SearchHit, SearchIndex};
+use crate::state::settings::{DailyNotesSettings, OpenTab, SavedSearch, Settings};
+use crate::state::tabs::{move_item, Tab, TabLabel};
+use crate::state::templates::expand_template;
 use crate::storage::{create_storage, PlatformStorage, StorageBackend};
+use chrono::{Duration, Local, NaiveDate};
 use dioxus::prelude::*;
+use std::collections::HashSet;
+
+/// How the open note is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ViewMode {
+    /// Markdown source only
+    #[default]
+    Source,
+    /// Source with the rendered note beside it
+    Preview,
+    /// Rendered in place, with the block being edited shown as source
+    Hybrid,
+}
+
+/// One side of a split editor. Without a split only `Primary` is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pane {
+    /// The left pane, showing the active tab
+    #[default]
+    Primary,
+    /// The right pane of a split
+    Secondary,
+}
 
 #[derive(Clone)]
 pub struct AppState {
     pub notes: Signal<Vec<NoteSummary>>,
     pub current_note: Signal<Option<Note>>,
+    /// Open notes in tab order; `current_note` is the one at `active_tab`
+    pub tabs: Signal<Vec<Tab>>,
+    pub active_tab: Signal<usize>,
+    /// Note in the right pane while the editor is split. When it is the
+    /// current note too, both panes edit `current_note` and this copy is
+    /// only brought up to date when the left pane moves on.
+    pub split_note: Signal<Option<Note>>,
+    pub split_save_status: Signal<SaveStatus>,
+    /// Pane the outline, sidebar and Vim status follow
+    pub focused_pane: Signal<Pane>,
     pub deleted_note: Signal<Option<Note>>,
     pub is_sidebar_visible: Signal<bool>,
-    pub is_preview_visible: Signal<bool>,
+    pub view_mode: Signal<ViewMode>,
     pub is_focus_mode: Signal<bool>,
     pub save_status: Signal<SaveStatus>,
+    pub backlinks: Signal<Vec<Backlink>>,
+    pub settings: Signal<Settings>,
+    pub keymap: Signal<Keymap>,
+    /// Notes as they were before the last find/replace, each with the
+    /// content the replace left, for undo
+    pub replaced_notes: Signal<Vec<(Note, String)>>,
+    search_index: Signal<SearchIndex>,
     storage: PlatformStorage,
 }
 
 impl AppState {
     pub fn new() -> Self {
         let storage = create_storage();
-        let notes = storage.list_notes();
+        let mut notes = storage.list_notes();
+        notes.sort_by_key(|note| !note.pinned);
+        let settings = storage.load_settings();
+        let keymap = storage.load_keymap();
+        let search_index =
+            SearchIndex::build(notes.iter().filter_map(|summary| storage.load_note(&summary.id)));
 
-        Self {
+        // Reopen last session's tabs, skipping notes deleted since
+        let tabs: Vec<Tab> = settings
+            .open_tabs
+            .iter()
+            .filter_map(|open| {
+                let note = storage.load_note(&open.note_id)?;
+                Some(Tab {
+                    selection: open.selection,
+                    scroll_top: open.scroll_top,
+                    ..Tab::new(note)
+                })
+            })
+            .collect();
+        let active_tab = settings.active_tab.min(tabs.len().saturating_sub(1));
+        let current_note = tabs.get(active_tab).map(|tab| tab.note.clone());
+
+        let mut state = Self {
             notes: Signal::new(notes),
-            current_note: Signal::new(None),
+            current_note: Signal::new(current_note),
+            tabs: Signal::new(tabs),
+            active_tab: Signal::new(active_tab),
+            split_note: Signal::new(None),
+            split_save_status: Signal::new(SaveStatus::Saved),
+            focused_pane: Signal::new(Pane::Primary),
             deleted_note: Signal::new(None),
             is_sidebar_visible: Signal::new(false),
-            is_preview_visible: Signal::new(false),
+            view_mode: Signal::new(ViewMode::Source),
             is_focus_mode: Signal::new(false),
             save_status: Signal::new(SaveStatus::Saved),
+            backlinks: Signal::new(Vec::new()),
+            settings: Signal::new(settings),
+            keymap: Signal::new(keymap),
+            replaced_notes: Signal::new(Vec::new()),
+            search_index: Signal::new(search_index),
             storage,
-        }
+        };
+        state.refresh_backlinks();
+        state
     }
 
     pub fn load_notes(&mut self) {
-        let notes = self.storage.list_notes();
+        let mut notes = self.storage.list_notes();
+        // Pinned notes first, otherwise keep the storage order
+        notes.sort_by_key(|note| !note.pinned);
         self.notes.set(notes);
     }
 
     pub fn create_note(&mut self) {
+        self.create_note_with_content(String::new());
+    }
+
+    pub fn create_note_with_content(&mut self, content: String) {
         // Save current note before creating new one
         self.save_current_note();
 
-        let note = Note::new();
+        let mut note = Note::new();
+        if !content.is_empty() {
+            note.content = content;
+            note.extract_title();
+        }
         if self.storage.save_note(&note).is_ok() {
             let id = note.id.clone();
+            self.search_index.write().upsert(&note);
             self.load_notes();
             // Load directly to avoid double-save
             if let Some(new_note) = self.storage.load_note(&id) {
-                self.current_note.set(Some(new_note));
-                self.save_status.set(SaveStatus::Saved);
+                self.open_note(new_note, true);
+            }
+        }
+    }
+
+    /// Create a note from the template note `template_id`. Returns the
+    /// `{{cursor}}` byte offset in the new note, if the template has one.
+    pub fn create_note_from_template(&mut self, template_id: &str) -> Option<usize> {
+        self.create_note_from_template_titled(template_id, None)
+    }
+
+    fn create_note_from_template_titled(&mut self, template_id: &str, title: Option<&str>) -> Option<usize> {
+        let template = self.storage.load_note(template_id)?;
+        let now = Local::now();
+        let (content, cursor) =
+            expand_template(&template.content, title, now.date_naive(), now.time());
+        self.create_note_with_content(content);
+        cursor
+    }
+
+    /// Ctrl+N: a blank note, or one from the default template when set.
+    pub fn create_default_note(&mut self) -> Option<usize> {
+        match self.default_template_id() {
+            Some(id) => self.create_note_from_template(&id),
+            None => {
+                self.create_note();
+                None
+            }
+        }
+    }
+
+    /// Create a note named `title`, from the default template when one is set.
+    pub fn create_note_titled(&mut self, title: &str) -> Option<usize> {
+        let title = title.trim();
+        let cursor = match self.default_template_id() {
+            Some(id) => self.create_note_from_template_titled(&id, Some(title)),
+            None => {
+                let content = format!("# {}\n\n", title);
+                let cursor = content.len();
+                self.create_note_with_content(content);
+                Some(cursor)
             }
+        };
+        // Templates without {{title}} would otherwise name the note after their first line
+        let needs_title = (self.current_note)().is_some_and(|note| note.title != title);
+        if needs_title {
+            self.set_custom_title(Some(title.to_string()));
+            self.save_current_note();
         }
+        cursor
+    }
+
+    fn default_template_id(&self) -> Option<String> {
+        (self.settings)()
+            .default_template
+            .filter(|id| self.templates().iter().any(|t| &t.id == id))
+    }
+
+    pub fn templates(&self) -> Vec<NoteSummary> {
+        (self.notes)().into_iter().filter(|n| n.is_template).collect()
     }
 
+    pub fn toggle_template(&mut self) {
+        if let Some(mut note) = (self.current_note)() {
+            note.is_template = !note.is_template;
+            self.current_note.set(Some(note));
+            self.save_current_note();
+        }
+    }
+
+    pub fn toggle_pinned(&mut self) {
+        if let Some(mut note) = (self.current_note)() {
+            note.pinned = !note.pinned;
+            self.current_note.set(Some(note));
+            self.save_current_note();
+        }
+    }
+
+    pub fn set_default_template(&mut self, template_id: Option<String>) {
+        let mut settings = (self.settings)();
+        settings.default_template = template_id;
+        self.update_settings(settings);
+    }
+
+    /// Show note `id` in the active tab, or switch to its tab when it is
+    /// already open.
     pub fn select_note(&mut self, id: &str) {
+        self.open_note_by_id(id, false);
+    }
+
+    /// Open note `id` in a new tab after the active one.
+    pub fn open_in_new_tab(&mut self, id: &str) {
+        self.open_note_by_id(id, true);
+    }
+
+    fn open_note_by_id(&mut self, id: &str, new_tab: bool) {
         // Save current note before switching
         self.save_current_note();
 
-        if let Some(note) = self.storage.load_note(id) {
-            self.current_note.set(Some(note));
-            self.save_status.set(SaveStatus::Saved);
+        if let Some(index) = (self.tabs)().iter().position(|tab| tab.note.id == id) {
+            self.switch_tab(index);
+        } else if let Some(note) = self.storage.load_note(id) {
+            self.open_note(note, new_tab);
+        }
+    }
+
+    /// Put `note` in the active tab, or in a new one after it.
+    fn open_note(&mut self, note: Note, new_tab: bool) {
+        self.stash_active_tab();
+        let mut tabs = (self.tabs)();
+        let active = (self.active_tab)();
+        let index = if new_tab || active >= tabs.len() {
+            let index = (active + 1).min(tabs.len());
+            tabs.insert(index, Tab::new(note));
+            index
+        } else {
+            tabs[active] = Tab::new(note);
+            active
+        };
+        self.tabs.set(tabs);
+        self.activate_tab(index);
+    }
+
+    /// Copy the live note and save status back into the active tab.
+    fn stash_active_tab(&mut self) {
+        let Some(note) = (self.current_note)() else { return };
+        let active = (self.active_tab)();
+        let save_status = (self.save_status)();
+        if self.split_shares_current() {
+            self.split_note.set(Some(note.clone()));
+            self.split_save_status.set(save_status);
+        }
+        if let Some(tab) = self.tabs.write().get_mut(active)
+            && tab.note.id == note.id
+        {
+            tab.note = note;
+            tab.save_status = save_status;
+        }
+    }
+
+    fn activate_tab(&mut self, index: usize) {
+        let Some(mut tab) = (self.tabs)().get(index).cloned() else { return };
+        // The right pane may have newer edits to the same note
+        let split = (self.split_note)().filter(|note| note.id == tab.note.id);
+        if let Some(note) = split.filter(|_| (self.split_save_status)() == SaveStatus::Modified) {
+            tab.note = note;
+            tab.save_status = SaveStatus::Modified;
         }
+        self.active_tab.set(index);
+        self.current_note.set(Some(tab.note));
+        self.save_status.set(tab.save_status);
+        self.refresh_backlinks();
+        self.persist_tabs();
+    }
+
+    /// Show the tab at `index`, keeping the unsaved edits of the one left.
+    pub fn switch_tab(&mut self, index: usize) {
+        if index != (self.active_tab)() || (self.current_note)().is_none() {
+            self.stash_active_tab();
+            self.activate_tab(index);
+        }
+    }
+
+    /// Ctrl+Tab and Ctrl+Shift+Tab: the next (`1`) or previous (`-1`) tab,
+    /// wrapping around.
+    pub fn cycle_tab(&mut self, step: isize) {
+        let len = (self.tabs)().len();
+        if len > 1 {
+            let index = ((self.active_tab)() as isize + step).rem_euclid(len as isize);
+            self.switch_tab(index as usize);
+        }
+    }
+
+    /// Close the tab at `index`, saving its note first.
+    pub fn close_tab(&mut self, index: usize) {
+        let active = (self.active_tab)();
+        if index == active {
+            self.save_current_note();
+            // Keep the tab rather than lose edits that didn't save
+            if (self.save_status)() == SaveStatus::Error {
+                return;
+            }
+        }
+        self.stash_active_tab();
+        let mut tabs = (self.tabs)();
+        if index >= tabs.len() {
+            return;
+        }
+        let closed = tabs.remove(index);
+        if index != active && closed.save_status == SaveStatus::Modified && self.save_note(&closed.note) {
+            self.load_notes();
+        }
+        self.tabs.set(tabs.clone());
+
+        if tabs.is_empty() {
+            self.active_tab.set(0);
+            self.current_note.set(None);
+            self.backlinks.set(Vec::new());
+            self.persist_tabs();
+        } else if index == active {
+            self.activate_tab(index.min(tabs.len() - 1));
+        } else {
+            self.active_tab.set(if index < active { active - 1 } else { active });
+            self.persist_tabs();
+        }
+    }
+
+    /// Drag a tab from `from` to `to`.
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        let mut tabs = (self.tabs)();
+        let active = move_item(&mut tabs, from, to, (self.active_tab)());
+        self.tabs.set(tabs);
+        self.active_tab.set(active);
+        self.persist_tabs();
+    }
+
+    /// Remember where the editor is in the active tab.
+    pub fn set_tab_position(&mut self, selection: (usize, usize), scroll_top: f64) {
+        let active = (self.active_tab)();
+        if let Some(tab) = self.tabs.write().get_mut(active) {
+            tab.selection = selection;
+            tab.scroll_top = scroll_top;
+        }
+        self.persist_tabs();
+    }
+
+    /// Selection and scroll offset to restore in the active tab.
+    pub fn active_tab_position(&self) -> Option<((usize, usize), f64)> {
+        (self.tabs)()
+            .get((self.active_tab)())
+            .map(|tab| (tab.selection, tab.scroll_top))
+    }
+
+    pub fn tab_labels(&self) -> Vec<TabLabel> {
+        let active = (self.active_tab)();
+        let current = (self.current_note)();
+        let save_status = (self.save_status)();
+        (self.tabs)()
+            .into_iter()
+            .enumerate()
+            .map(|(i, tab)| match current.as_ref().filter(|_| i == active) {
+                Some(note) => TabLabel {
+                    title: note.title.clone(),
+                    is_modified: save_status == SaveStatus::Modified,
+                },
+                None => TabLabel {
+                    title: tab.note.title,
+                    is_modified: tab.save_status == SaveStatus::Modified,
+                },
+            })
+            .collect()
+    }
+
+    /// Record the open tabs in the settings so they reopen on restart.
+    fn persist_tabs(&mut self) {
+        let mut settings = (self.settings)();
+        settings.open_tabs = (self.tabs)()
+            .into_iter()
+            .map(|tab| OpenTab {
+                note_id: tab.note.id,
+                selection: tab.selection,
+                scroll_top: tab.scroll_top,
+            })
+            .collect();
+        settings.active_tab = (self.active_tab)();
+        if settings != (self.settings)() {
+            self.update_settings(settings);
+        }
+    }
+
+    /// Open the daily note for `date`, creating it from the template if needed.
+    /// Returns the template's `{{cursor}}` offset when a note was created.
+    pub fn open_daily_note(&mut self, date: NaiveDate) -> Option<usize> {
+        let settings = (self.settings)().daily_notes;
+        let existing = find_daily_note(&(self.notes)(), &settings, date).map(|n| n.id.clone());
+        match existing {
+            Some(id) => {
+                self.select_note(&id);
+                None
+            }
+            None => {
+                let (content, cursor) = daily_content(&settings, date);
+                self.create_note_with_content(content);
+                cursor
+            }
+        }
+    }
+
+    /// The date of the current note when it is a daily note.
+    pub fn current_daily_date(&self) -> Option<NaiveDate> {
+        let settings = (self.settings)().daily_notes;
+        (self.current_note)().and_then(|note| daily_date(&settings, &note.title))
+    }
+
+    /// Move to the previous (`-1`) or next (`1`) day from the current daily note.
+    pub fn open_adjacent_daily_note(&mut self, days: i64) -> Option<usize> {
+        let date = self.current_daily_date()?;
+        self.open_daily_note(date + Duration::days(days))
+    }
+
+    pub fn update_daily_notes_settings(&mut self, daily_notes: DailyNotesSettings) {
+        let mut settings = (self.settings)();
+        settings.daily_notes = daily_notes;
+        self.update_settings(settings);
+    }
+
+    /// Save `query` as a smart folder, replacing one with the same name.
+    pub fn save_search(&mut self, name: &str, query: &str) {
+        let name = name.trim();
+        let query = query.trim();
+        if name.is_empty() || query.is_empty() {
+            return;
+        }
+        let mut settings = (self.settings)();
+        let saved = SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+        };
+        match settings.saved_searches.iter_mut().find(|s| s.name == name) {
+            Some(existing) => *existing = saved,
+            None => settings.saved_searches.push(saved),
+        }
+        self.update_settings(settings);
+    }
+
+    pub fn delete_saved_search(&mut self, name: &str) {
+        let mut settings = (self.settings)();
+        settings.saved_searches.retain(|s| s.name != name);
+        self.update_settings(settings);
+    }
+
+    /// Every saved search with all of its current results. Reading this in
+    /// a memo keeps smart folders live as notes are saved.
+    pub fn smart_folders(&self) -> Vec<(SavedSearch, Vec<SearchHit>)> {
+        (self.settings)()
+            .saved_searches
+            .into_iter()
+            .map(|saved| {
+                let hits = parse_query(&saved.query)
+                    .map(|query| self.search_index.read().search_all(&query))
+                    .unwrap_or_default();
+                (saved, hits)
+            })
+            .collect()
+    }
+
+    pub fn update_settings(&mut self, settings: Settings) {
+        let _ = self.storage.save_settings(&settings);
+        self.settings.set(settings);
+    }
+
+    pub fn update_keymap(&mut self, keymap: Keymap) {
+        let _ = self.storage.save_keymap(&keymap);
+        self.keymap.set(keymap);
     }
 
     pub fn update_content(&mut self, content: String) {
-        if let Some(mut note) = (self.current_note)() {
-            note.content = content;
+        self.update_pane_content(Pane::Primary, content);
+    }
+
+    pub fn update_pane_content(&mut self, pane: Pane, content: String) {
+        self.edit_pane_note(pane, |note| note.content = content);
+    }
+
+    /// Set or clear (`None`) the title typed in the UI.
+    pub fn set_custom_title(&mut self, title: Option<String>) {
+        self.set_pane_title(Pane::Primary, title);
+    }
+
+    pub fn set_pane_title(&mut self, pane: Pane, title: Option<String>) {
+        self.edit_pane_note(pane, |note| {
+            note.custom_title = title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty());
+        });
+    }
+
+    /// Move the note in `pane` to `folder`, or out of any folder (`None`).
+    pub fn set_pane_folder(&mut self, pane: Pane, folder: Option<String>) {
+        self.edit_pane_note(pane, |note| {
+            note.folder = folder
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty());
+        });
+    }
+
+    /// Folders that saved notes are in, sorted.
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders: Vec<String> = self
+            .search_index
+            .read()
+            .notes()
+            .filter_map(|note| note.folder.clone())
+            .collect();
+        folders.sort();
+        folders.dedup();
+        folders
+    }
+
+    fn edit_pane_note(&mut self, pane: Pane, edit: impl FnOnce(&mut Note)) {
+        let (mut note_signal, mut status_signal) = if pane == Pane::Secondary && !self.split_shares_current() {
+            (self.split_note, self.split_save_status)
+        } else {
+            (self.current_note, self.save_status)
+        };
+        if let Some(mut note) = note_signal() {
+            edit(&mut note);
             note.extract_title();
             note.touch();
-            self.current_note.set(Some(note));
-            self.save_status.set(SaveStatus::Modified);
+            note_signal.set(Some(note));
+            status_signal.set(SaveStatus::Modified);
+        }
+    }
+
+    /// Split the editor, showing the current note in both panes, or close
+    /// the split.
+    pub fn toggle_split(&mut self) {
+        if (self.split_note)().is_some() {
+            self.close_split();
+        } else if let Some(note) = (self.current_note)() {
+            self.split_note.set(Some(note));
+            self.split_save_status.set((self.save_status)());
+        }
+    }
+
+    pub fn close_split(&mut self) {
+        self.save_split_note();
+        self.split_note.set(None);
+        self.split_save_status.set(SaveStatus::Saved);
+        self.focused_pane.set(Pane::Primary);
+    }
+
+    /// Show note `id` in the right pane.
+    pub fn open_in_split(&mut self, id: &str) {
+        self.save_split_note();
+        let current = (self.current_note)().filter(|note| note.id == id);
+        if let Some(note) = current {
+            self.split_note.set(Some(note));
+            self.split_save_status.set((self.save_status)());
+        } else if let Some(note) = self.storage.load_note(id) {
+            self.split_note.set(Some(note));
+            self.split_save_status.set(SaveStatus::Saved);
+        }
+    }
+
+    /// Whether both panes show the current note.
+    fn split_shares_current(&self) -> bool {
+        let split_id = (self.split_note)().map(|note| note.id);
+        split_id.is_some() && split_id == (self.current_note)().map(|note| note.id)
+    }
+
+    /// The note shown in `pane`.
+    pub fn pane_note(&self, pane: Pane) -> Option<Note> {
+        if pane == Pane::Secondary && !self.split_shares_current() {
+            (self.split_note)()
+        } else {
+            (self.current_note)()
+        }
+    }
+
+    pub fn pane_save_status(&self, pane: Pane) -> SaveStatus {
+        if pane == Pane::Secondary && !self.split_shares_current() {
+            (self.split_save_status)()
+        } else {
+            (self.save_status)()
+        }
+    }
+
+    /// `:w` and other explicit saves from one pane.
+    pub fn save_pane(&mut self, pane: Pane) {
+        if pane == Pane::Secondary && !self.split_shares_current() {
+            self.save_split_note();
+        } else {
+            self.save_current_note();
+        }
+    }
+
+    pub fn focus_pane(&mut self, pane: Pane) {
+        if (self.focused_pane)() != pane {
+            self.focused_pane.set(pane);
+        }
+    }
+
+    /// Save the right pane's note when it has its own unsaved edits.
+    fn save_split_note(&mut self) {
+        if self.split_shares_current() || (self.split_save_status)() != SaveStatus::Modified {
+            return;
+        }
+        if let Some(note) = (self.split_note)() {
+            self.split_save_status.set(SaveStatus::Saving);
+            if self.save_note(&note) {
+                self.split_save_status.set(SaveStatus::Saved);
+                self.refresh_tabs(&note);
+                self.load_notes();
+                self.refresh_backlinks();
+            } else {
+                self.split_save_status.set(SaveStatus::Error);
+            }
         }
     }
 
     pub fn save_current_note(&mut self) {
         if let Some(note) = (self.current_note)() {
             self.save_status.set(SaveStatus::Saving);
-            if self.storage.save_note(&note).is_ok() {
+            if self.save_note(&note) {
                 self.save_status.set(SaveStatus::Saved);
+                self.refresh_tabs(&note);
                 self.load_notes();
+                self.refresh_backlinks();
             } else {
                 self.save_status.set(SaveStatus::Error);
             }
         }
     }
 
+    /// Give every tab holding `note` the copy just saved, so switching to
+    /// one doesn't bring back older text.
+    fn refresh_tabs(&mut self, note: &Note) {
+        if !(self.tabs)().iter().any(|tab| tab.note.id == note.id) {
+            return;
+        }
+        for tab in self.tabs.write().iter_mut().filter(|tab| tab.note.id == note.id) {
+            tab.note = note.clone();
+            tab.save_status = SaveStatus::Saved;
+        }
+    }
+
+    /// Save the open note, the right pane's and any other tab with unsaved
+    /// edits.
+    pub fn save_open_notes(&mut self) {
+        self.save_current_note();
+        self.save_split_note();
+        let active = (self.active_tab)();
+        let pending: Vec<(usize, Note)> = (self.tabs)()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, tab)| *i != active && tab.save_status == SaveStatus::Modified)
+            .map(|(i, tab)| (i, tab.note))
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        for (i, note) in pending {
+            let save_status = if self.save_note(&note) { SaveStatus::Saved } else { SaveStatus::Error };
+            if let Some(tab) = self.tabs.write().get_mut(i) {
+                tab.save_status = save_status;
+            }
+        }
+        self.load_notes();
+    }
+
+    fn save_note(&mut self, note: &Note) -> bool {
+        let saved = self.storage.save_note(note).is_ok();
+        if saved {
+            self.search_index.write().upsert(note);
+        }
+        saved
+    }
+
     pub fn delete_current_note(&mut self) {
-        if let Some(note) = (self.current_note)() {
-            if self.storage.delete_note(&note.id).is_ok() {
-                self.deleted_note.set(Some(note));
-                self.current_note.set(None);
-                self.load_notes();
+        if let Some(note) = (self.current_note)()
+            && self.storage.delete_note(&note.id).is_ok()
+        {
+            self.search_index.write().remove(&note.id);
+            if self.split_shares_current() {
+                self.split_note.set(None);
+                self.focused_pane.set(Pane::Primary);
+            }
+            self.deleted_note.set(Some(note));
+            self.current_note.set(None);
+            self.backlinks.set(Vec::new());
+            self.load_notes();
 
-                // Select first note if available
-                let notes = (self.notes)();
-                if let Some(first) = notes.first() {
-                    self.select_note(&first.id);
-                }
+            let mut tabs = (self.tabs)();
+            let active = (self.active_tab)();
+            if active < tabs.len() {
+                tabs.remove(active);
+            }
+            self.tabs.set(tabs.clone());
+
+            // Show the next tab, or the first note if none are left
+            let notes = (self.notes)();
+            if !tabs.is_empty() {
+                self.activate_tab(active.min(tabs.len() - 1));
+            } else if let Some(first) = notes.first() {
+                self.select_note(&first.id);
+            } else {
+                self.persist_tabs();
             }
         }
     }
 
-    pub fn undo_delete(&mut self) {
-        if let Some(note) = (self.deleted_note)() {
+    /// Recompute which notes link to or mention the current note.
+    pub fn refresh_backlinks(&mut self) {
+        let backlinks = match (self.current_note)() {
+            Some(note) => find_backlinks(&note, &self.load_all_notes()),
+            None => Vec::new(),
+        };
+        self.backlinks.set(backlinks);
+    }
+
+    pub fn note_graph(&self) -> NoteGraph {
+        NoteGraph::build(&self.load_all_notes())
+    }
+
+    /// Search saved notes with the query language (`tag:work "a phrase" -draft`).
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>, QueryError> {
+        let query = parse_query(query)?;
+        Ok(self.search_index.read().search(&query))
+    }
+
+    /// Last saved contents of a note, without touching storage.
+    pub fn note_content(&self, id: &str) -> Option<String> {
+        self.search_index.read().get(id).map(|note| note.content.clone())
+    }
+
+    /// Matches of `query` in every note, including unsaved edits to the
+    /// open note.
+    pub fn find_in_notes(&self, query: &ReplaceQuery) -> Result<Vec<NoteMatches>, String> {
+        let mut notes = self.load_all_notes();
+        let split = (self.split_note)().filter(|_| (self.split_save_status)() == SaveStatus::Modified);
+        let unsaved = (self.tabs)()
+            .into_iter()
+            .filter(|tab| tab.save_status == SaveStatus::Modified)
+            .map(|tab| tab.note)
+            .chain(split)
+            .chain((self.current_note)());
+        for open in unsaved {
+            if let Some(note) = notes.iter_mut().find(|n| n.id == open.id) {
+                *note = open;
+            }
+        }
+        notes.sort_by_key(|note| std::cmp::Reverse(note.updated_at));
+        find_matches(&notes, query)
+    }
+
+    /// Replace every match of `query` except `excluded` (note id, match
+    /// start) and save the affected notes. Returns the number of matches
+    /// replaced; the previous versions are kept for `undo_replace`.
+    pub fn replace_in_notes(&mut self, query: &ReplaceQuery, excluded: &HashSet<(String, usize)>) -> usize {
+        self.save_open_notes();
+        let Ok(results) = self.find_in_notes(query) else { return 0 };
+
+        let mut originals = Vec::new();
+        let mut replaced = 0;
+        for result in results {
+            let Some(mut note) = self.storage.load_note(&result.note_id) else { continue };
+            let included: Vec<_> = result
+                .matches
+                .iter()
+                .filter(|m| !excluded.contains(&(note.id.clone(), m.start)))
+                .collect();
+            if included.is_empty() {
+                continue;
+            }
+
+            let original = note.clone();
+            note.content = apply_matches(&note.content, included.iter().copied());
+            note.extract_title();
+            note.touch();
             if self.storage.save_note(&note).is_ok() {
-                self.deleted_note.set(None);
-                self.load_notes();
-                self.select_note(&note.id);
+                replaced += included.len();
+                self.search_index.write().upsert(&note);
+                originals.push((original, note.content.clone()));
+                self.reload_if_current(&note);
+            }
+        }
+
+        if !originals.is_empty() {
+            self.replaced_notes.set(originals);
+            self.load_notes();
+            self.refresh_backlinks();
+        }
+        replaced
+    }
+
+    /// Put back the notes changed by the last `replace_in_notes`. Notes
+    /// edited since are left alone, so those edits aren't lost; returns their
+    /// titles.
+    pub fn undo_replace(&mut self) -> Vec<String> {
+        let mut edited = Vec::new();
+        for (note, replaced) in (self.replaced_notes)() {
+            match self.latest_content(&note.id) {
+                Some(content) if content == replaced => {
+                    if self.storage.save_note(&note).is_err() {
+                        continue;
+                    }
+                    self.search_index.write().upsert(&note);
+                    self.reload_if_current(&note);
+                }
+                Some(_) => edited.push(note.title),
+                None => {}
             }
         }
+        self.replaced_notes.set(Vec::new());
+        self.load_notes();
+        self.refresh_backlinks();
+        edited
+    }
+
+    /// Content of note `id` with any unsaved edits in the panes or tabs.
+    fn latest_content(&self, id: &str) -> Option<String> {
+        let tabs = (self.tabs)();
+        (self.current_note)()
+            .into_iter()
+            .chain((self.split_note)())
+            .chain(tabs.into_iter().map(|tab| tab.note))
+            .find(|note| note.id == id)
+            .map(|note| note.content)
+            .or_else(|| self.note_content(id))
+    }
+
+    fn reload_if_current(&mut self, note: &Note) {
+        let is_current = (self.current_note)().is_some_and(|current| current.id == note.id);
+        if is_current {
+            self.current_note.set(Some(note.clone()));
+            self.save_status.set(SaveStatus::Saved);
+        }
+        if (self.split_note)().is_some_and(|split| split.id == note.id) {
+            self.split_note.set(Some(note.clone()));
+            self.split_save_status.set(SaveStatus::Saved);
+        }
+        for tab in self.tabs.write().iter_mut().filter(|tab| tab.note.id == note.id) {
+            tab.note = note.clone();
+            tab.save_status = SaveStatus::Saved;
+        }
+    }
+
+    /// Every saved note, served from the search index instead of storage.
+    fn load_all_notes(&self) -> Vec<Note> {
+        self.search_index.read().notes().cloned().collect()
+    }
+
+    pub fn undo_delete(&mut self) {
+        if let Some(note) = (self.deleted_note)()
+            && self.storage.save_note(&note).is_ok()
+        {
+            self.search_index.write().upsert(&note);
+            self.deleted_note.set(None);
+            self.load_notes();
+            self.select_note(&note.id);
+        }
     }
 
     pub fn clear_deleted_note(&mut self) {
@@ -120,14 +895,29 @@ impl AppState {
     }
 
     pub fn toggle_preview(&mut self) {
-        let current = (self.is_preview_visible)();
-        self.is_preview_visible.set(!current);
+        self.toggle_view_mode(ViewMode::Preview);
+    }
+
+    pub fn toggle_hybrid(&mut self) {
+        self.toggle_view_mode(ViewMode::Hybrid);
+    }
+
+    /// Switch to `mode`, or back to source when it is already on.
+    fn toggle_view_mode(&mut self, mode: ViewMode) {
+        let current = (self.view_mode)();
+        self.view_mode.set(if current == mode { ViewMode::Source } else { mode });
     }
 
     pub fn toggle_focus_mode(&mut self) {
         let current = (self.is_focus_mode)();
         self.is_focus_mode.set(!current);
     }
+
+    pub fn toggle_vim_mode(&mut self) {
+        let mut settings = (self.settings)();
+        settings.vim_mode = !settings.vim_mode;
+        self.update_settings(settings);
+    }
 }
 
 impl Default for AppState {
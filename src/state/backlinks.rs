@@ -0,0 +1,74 @@
+use crate::markdown::extract_links;
+use crate::state::note::Note;
+use std::collections::HashSet;
+
+const CONTEXT_MAX_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BacklinkKind {
+    /// The source note links here with `[[Title]]` or `[label](id)`
+    Linked,
+    /// The source note mentions the title without linking it
+    Unlinked,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlink {
+    pub note_id: String,
+    pub note_title: String,
+    pub line: usize,
+    pub context: String,
+    pub kind: BacklinkKind,
+}
+
+pub fn find_backlinks(target: &Note, notes: &[Note]) -> Vec<Backlink> {
+    let title = target.title.to_lowercase();
+    let check_mentions = !title.is_empty() && target.title != "Untitled";
+    let mut backlinks = Vec::new();
+
+    for note in notes.iter().filter(|n| n.id != target.id) {
+        let linked_lines: HashSet<usize> = extract_links(&note.content)
+            .into_iter()
+            .filter(|link| link.target.points_to(&target.id, &target.title))
+            .map(|link| link.line)
+            .collect();
+
+        for (line_num, line) in note.content.lines().enumerate() {
+            let kind = if linked_lines.contains(&line_num) {
+                BacklinkKind::Linked
+            } else if check_mentions && contains_word(&line.to_lowercase(), &title) {
+                BacklinkKind::Unlinked
+            } else {
+                continue;
+            };
+
+            backlinks.push(Backlink {
+                note_id: note.id.clone(),
+                note_title: note.title.clone(),
+                line: line_num,
+                context: truncate_context(line.trim()),
+                kind,
+            });
+        }
+    }
+
+    backlinks
+}
+
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let end = start + needle.len();
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn truncate_context(line: &str) -> String {
+    if line.chars().count() > CONTEXT_MAX_CHARS {
+        let truncated: String = line.chars().take(CONTEXT_MAX_CHARS - 3).collect();
+        format!("{}...", truncated)
+    } else {
+        line.to_string()
+    }
+}
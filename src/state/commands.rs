@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Every action the app can run from a shortcut, a button or the command
+/// palette. Serialized by its kebab-case name in the keymap file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+// `CommandPalette` is named after what it opens
+#[allow(clippy::enum_variant_names)]
+pub enum Command {
+    NewNote,
+    NewNoteFromTemplate,
+    QuickSwitcher,
+    CommandPalette,
+    NextTab,
+    PreviousTab,
+    CloseTab,
+    ToggleSidebar,
+    TogglePreview,
+    ToggleHybrid,
+    ToggleSplit,
+    ToggleFocusMode,
+    ToggleVimMode,
+    OpenToday,
+    PreviousDailyNote,
+    NextDailyNote,
+    ToggleCalendar,
+    ToggleGraph,
+    TogglePinned,
+    ToggleTemplate,
+    PromoteSection,
+    DemoteSection,
+    DeleteNote,
+    IncreaseFontSize,
+    DecreaseFontSize,
+    StartPauseTimer,
+    ResetTimer,
+    Timer5,
+    Timer15,
+    Timer25,
+    Timer45,
+    ExportMarkdown,
+    ExportHtml,
+    OpenKeybindings,
+    FindReplace,
+    FindInNote,
+    Bold,
+    Italic,
+    InlineCode,
+    Strikethrough,
+    InsertLink,
+    FoldSection,
+    UnfoldSection,
+}
+
+/// Where a binding applies. Editor bindings take precedence over global
+/// ones while the editor has focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Global,
+    Editor,
+}
+
+impl Command {
+    pub const ALL: &'static [Command] = &[
+        Command::NewNote,
+        Command::NewNoteFromTemplate,
+        Command::QuickSwitcher,
+        Command::CommandPalette,
+        Command::NextTab,
+        Command::PreviousTab,
+        Command::CloseTab,
+        Command::ToggleSidebar,
+        Command::TogglePreview,
+        Command::ToggleHybrid,
+        Command::ToggleSplit,
+        Command::ToggleFocusMode,
+        Command::ToggleVimMode,
+        Command::OpenToday,
+        Command::PreviousDailyNote,
+        Command::NextDailyNote,
+        Command::ToggleCalendar,
+        Command::ToggleGraph,
+        Command::TogglePinned,
+        Command::ToggleTemplate,
+        Command::PromoteSection,
+        Command::DemoteSection,
+        Command::DeleteNote,
+        Command::IncreaseFontSize,
+        Command::DecreaseFontSize,
+        Command::StartPauseTimer,
+        Command::ResetTimer,
+        Command::Timer5,
+        Command::Timer15,
+        Command::Timer25,
+        Command::Timer45,
+        Command::ExportMarkdown,
+        Command::ExportHtml,
+        Command::OpenKeybindings,
+        Command::FindReplace,
+        Command::FindInNote,
+        Command::Bold,
+        Command::Italic,
+        Command::InlineCode,
+        Command::Strikethrough,
+        Command::InsertLink,
+        Command::FoldSection,
+        Command::UnfoldSection,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Command::NewNote => "New note",
+            Command::NewNoteFromTemplate => "New note from template",
+            Command::QuickSwitcher => "Open quick switcher",
+            Command::CommandPalette => "Command palette",
+            Command::NextTab => "Next tab",
+            Command::PreviousTab => "Previous tab",
+            Command::CloseTab => "Close tab",
+            Command::ToggleSidebar => "Toggle notes list",
+            Command::TogglePreview => "Toggle preview",
+            Command::ToggleHybrid => "Toggle live preview",
+            Command::ToggleSplit => "Toggle split editor",
+            Command::ToggleFocusMode => "Toggle focus mode",
+            Command::ToggleVimMode => "Toggle Vim keybindings",
+            Command::OpenToday => "Open today's note",
+            Command::PreviousDailyNote => "Previous daily note",
+            Command::NextDailyNote => "Next daily note",
+            Command::ToggleCalendar => "Daily notes calendar",
+            Command::ToggleGraph => "Graph view",
+            Command::TogglePinned => "Pin or unpin note",
+            Command::ToggleTemplate => "Use note as template",
+            Command::PromoteSection => "Promote section heading",
+            Command::DemoteSection => "Demote section heading",
+            Command::DeleteNote => "Delete note",
+            Command::IncreaseFontSize => "Larger text",
+            Command::DecreaseFontSize => "Smaller text",
+            Command::StartPauseTimer => "Start or pause timer",
+            Command::ResetTimer => "Reset timer",
+            Command::Timer5 => "Set timer to 5 minutes",
+            Command::Timer15 => "Set timer to 15 minutes",
+            Command::Timer25 => "Set timer to 25 minutes",
+            Command::Timer45 => "Set timer to 45 minutes",
+            Command::ExportMarkdown => "Export note as Markdown",
+            Command::ExportHtml => "Export note as HTML",
+            Command::OpenKeybindings => "Keyboard shortcuts",
+            Command::FindReplace => "Find and replace in all notes",
+            Command::FindInNote => "Find in note",
+            Command::Bold => "Bold",
+            Command::Italic => "Italic",
+            Command::InlineCode => "Inline code",
+            Command::Strikethrough => "Strikethrough",
+            Command::InsertLink => "Insert link",
+            Command::FoldSection => "Fold section",
+            Command::UnfoldSection => "Unfold section",
+        }
+    }
+
+    pub fn default_shortcuts(self) -> Vec<Shortcut> {
+        let shortcuts: &[&str] = match self {
+            Command::NewNote => &["Ctrl+N"],
+            Command::NewNoteFromTemplate => &["Ctrl+Shift+N"],
+            Command::QuickSwitcher => &["Ctrl+K", "Ctrl+O"],
+            Command::CommandPalette => &["Ctrl+Shift+P"],
+            Command::NextTab => &["Ctrl+Tab"],
+            Command::PreviousTab => &["Ctrl+Shift+Tab"],
+            Command::CloseTab => &["Ctrl+W"],
+            Command::ToggleSidebar => &["Ctrl+B", "Ctrl+\\"],
+            Command::TogglePreview => &["Ctrl+P"],
+            Command::ToggleHybrid => &["Ctrl+Shift+E"],
+            Command::ToggleSplit => &["Ctrl+Shift+S"],
+            Command::ToggleFocusMode => &["Ctrl+Shift+F"],
+            Command::OpenToday => &["Ctrl+D"],
+            // Shift+, and Shift+. on US layouts; Alt+Arrow moves by word
+            Command::PreviousDailyNote => &["Ctrl+Shift+<"],
+            Command::NextDailyNote => &["Ctrl+Shift+>"],
+            // Alt+Shift+Arrow extends the selection by word
+            Command::PromoteSection => &["Ctrl+="],
+            Command::DemoteSection => &["Ctrl+-"],
+            Command::ToggleCalendar => &["Ctrl+Shift+D"],
+            Command::ToggleGraph => &["Ctrl+G"],
+            Command::OpenKeybindings => &["Ctrl+,"],
+            Command::FindReplace => &["Ctrl+Shift+H"],
+            Command::FindInNote => &["Ctrl+F"],
+            Command::Bold => &["Ctrl+B"],
+            Command::Italic => &["Ctrl+I"],
+            Command::InlineCode => &["Ctrl+E"],
+            Command::Strikethrough => &["Ctrl+Shift+X"],
+            Command::InsertLink => &["Ctrl+K"],
+            // Shift+[ and Shift+] on US layouts
+            Command::FoldSection => &["Ctrl+Shift+{"],
+            Command::UnfoldSection => &["Ctrl+Shift+}"],
+            _ => &[],
+        };
+        shortcuts.iter().filter_map(|s| Shortcut::parse(s)).collect()
+    }
+
+    pub fn context(self) -> KeyContext {
+        match self {
+            Command::FindInNote
+            | Command::Bold
+            | Command::Italic
+            | Command::InlineCode
+            | Command::Strikethrough
+            | Command::InsertLink
+            | Command::FoldSection
+            | Command::UnfoldSection => KeyContext::Editor,
+            _ => KeyContext::Global,
+        }
+    }
+
+    /// Whether the command acts on the open note.
+    pub fn needs_note(self) -> bool {
+        matches!(
+            self,
+            Command::NextTab
+                | Command::PreviousTab
+                | Command::CloseTab
+                | Command::TogglePreview
+                | Command::ToggleHybrid
+                | Command::ToggleSplit
+                | Command::TogglePinned
+                | Command::ToggleTemplate
+                | Command::PromoteSection
+                | Command::DemoteSection
+                | Command::DeleteNote
+                | Command::IncreaseFontSize
+                | Command::DecreaseFontSize
+                | Command::ExportMarkdown
+                | Command::ExportHtml
+        )
+    }
+
+    /// Timer length set by the preset commands, in seconds.
+    pub fn timer_preset(self) -> Option<u64> {
+        match self {
+            Command::Timer5 => Some(5 * 60),
+            Command::Timer15 => Some(15 * 60),
+            Command::Timer25 => Some(25 * 60),
+            Command::Timer45 => Some(45 * 60),
+            _ => None,
+        }
+    }
+}
+
+/// A key with modifiers, like `Ctrl+Shift+F`. Ctrl also stands for Cmd on
+/// macOS. Letter keys are stored lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: String,
+}
+
+impl Shortcut {
+    pub fn new(ctrl: bool, shift: bool, alt: bool, key: &str) -> Self {
+        let key = if key == " " {
+            String::from("Space")
+        } else if key.chars().count() == 1 {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        };
+        Self { ctrl, shift, alt, key }
+    }
+
+    /// Parses `Ctrl+Shift+F` style text; modifiers are case-insensitive.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        // "Ctrl++" binds the plus key itself
+        if text.ends_with("++") {
+            parts.truncate(parts.len().saturating_sub(2));
+            parts.push("+");
+        }
+        let key = parts.pop().filter(|k| !k.is_empty())?;
+
+        let (mut ctrl, mut shift, mut alt) = (false, false, false);
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" | "meta" => ctrl = true,
+                "shift" => shift = true,
+                "alt" | "option" => alt = true,
+                _ => return None,
+            }
+        }
+        Some(Self::new(ctrl, shift, alt, key))
+    }
+
+    /// Display form, with arrows for arrow keys.
+    pub fn label(&self) -> String {
+        let text = self.to_string();
+        match self.key.as_str() {
+            "ArrowLeft" => text.replace("ArrowLeft", "←"),
+            "ArrowRight" => text.replace("ArrowRight", "→"),
+            "ArrowUp" => text.replace("ArrowUp", "↑"),
+            "ArrowDown" => text.replace("ArrowDown", "↓"),
+            _ => text,
+        }
+    }
+
+    /// Keys that only make sense with a modifier held, so typing still works.
+    pub fn is_valid_binding(&self) -> bool {
+        let is_function_key = self.key.len() > 1
+            && self.key.starts_with('F')
+            && self.key[1..].chars().all(|c| c.is_ascii_digit());
+        !matches!(self.key.as_str(), "Control" | "Shift" | "Alt" | "Meta")
+            && (self.ctrl || self.alt || is_function_key)
+    }
+}
+
+impl TryFrom<String> for Shortcut {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text).ok_or_else(|| format!("Invalid shortcut {}", text))
+    }
+}
+
+impl From<Shortcut> for String {
+    fn from(shortcut: Shortcut) -> Self {
+        shortcut.to_string()
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        match self.key.as_str() {
+            key if key.chars().count() == 1 => write!(f, "{}", key.to_uppercase()),
+            key => write!(f, "{}", key),
+        }
+    }
+}
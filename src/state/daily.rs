@@ -0,0 +1,56 @@
+use crate::state::note::{Note, NoteSummary};
+use crate::state::settings::DailyNotesSettings;
+use crate::state::templates::expand_template;
+use chrono::{Local, NaiveDate};
+use std::fmt::Write;
+
+const FALLBACK_FORMAT: &str = "%Y-%m-%d";
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Title of the daily note for `date`. Falls back to ISO dates when the
+/// configured format is invalid instead of panicking inside chrono.
+pub fn daily_title(settings: &DailyNotesSettings, date: NaiveDate) -> String {
+    let mut title = String::new();
+    if write!(title, "{}", date.format(&settings.title_format)).is_err() || title.trim().is_empty() {
+        return date.format(FALLBACK_FORMAT).to_string();
+    }
+    title
+}
+
+/// Content of a new daily note and the `{{cursor}}` offset within it.
+pub fn daily_content(settings: &DailyNotesSettings, date: NaiveDate) -> (String, Option<usize>) {
+    let title = daily_title(settings, date);
+    let (content, cursor) = expand_template(&settings.template, Some(&title), date, Local::now().time());
+
+    // The note is found again by its title, so make sure the template yields it
+    let mut note = Note::new();
+    note.content = content.clone();
+    note.extract_title();
+    if note.title == title {
+        (content, cursor)
+    } else {
+        let heading = format!("# {}\n\n", title);
+        let cursor = cursor.map(|offset| offset + heading.len());
+        (format!("{}{}", heading, content), cursor)
+    }
+}
+
+/// The date a note stands for, if its title is a daily note title.
+pub fn daily_date(settings: &DailyNotesSettings, title: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(title, &settings.title_format)
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(title, FALLBACK_FORMAT).ok())
+        .filter(|date| daily_title(settings, *date) == title)
+}
+
+pub fn find_daily_note<'a>(
+    notes: &'a [NoteSummary],
+    settings: &DailyNotesSettings,
+    date: NaiveDate,
+) -> Option<&'a NoteSummary> {
+    let title = daily_title(settings, date);
+    notes.iter().find(|note| note.title == title)
+}
@@ -0,0 +1,137 @@
+// Scoring for a fuzzy subsequence match
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 18;
+const BONUS_FIRST_CHAR: i64 = 24;
+const BONUS_WORD_START: i64 = 16;
+const BONUS_CAMEL_CASE: i64 = 10;
+const PENALTY_GAP_START: i64 = 6;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+const PENALTY_LEADING_CHAR: i64 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices into the text of the matched characters
+    pub indices: Vec<usize>,
+}
+
+/// Best-scoring alignment of `query` as a case-insensitive subsequence of
+/// `text`, favoring contiguous runs and matches at word starts. Whitespace in
+/// the query is ignored. Runs in O(text × query).
+pub fn fuzzy_match(text: &str, query: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+
+    let original: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let (n, m) = (lower.len(), query.len());
+    if m > n {
+        return None;
+    }
+
+    // Cheap rejection before the table is allocated
+    let mut next = 0;
+    for c in &lower {
+        if next < m && *c == query[next] {
+            next += 1;
+        }
+    }
+    if next < m {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..n)
+        .map(|i| {
+            if i == 0 {
+                BONUS_FIRST_CHAR
+            } else if !original[i - 1].is_alphanumeric() && original[i].is_alphanumeric() {
+                BONUS_WORD_START
+            } else if original[i - 1].is_lowercase() && original[i].is_uppercase() {
+                BONUS_CAMEL_CASE
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    // score[j][i]: best score with query[j] matched at text[i]
+    // from[j][i]: where query[j - 1] was matched on that path
+    let mut score = vec![vec![None::<i64>; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+
+    for i in 0..n {
+        if lower[i] == query[0] {
+            score[0][i] = Some(SCORE_MATCH + bonus[i] - PENALTY_LEADING_CHAR * i as i64);
+        }
+    }
+
+    for j in 1..m {
+        // Best predecessor at least two chars back, with the gap penalty applied
+        let mut gap_best: Option<(i64, usize)> = None;
+        for i in 1..n {
+            if i >= 2 {
+                let candidate = score[j - 1][i - 2].map(|s| (s - PENALTY_GAP_START, i - 2));
+                gap_best = match (gap_best, candidate) {
+                    (Some((g, at)), Some((c, c_at))) => {
+                        let extended = g - PENALTY_GAP_EXTENSION;
+                        Some(if c >= extended { (c, c_at) } else { (extended, at) })
+                    }
+                    (Some((g, at)), None) => Some((g - PENALTY_GAP_EXTENSION, at)),
+                    (None, candidate) => candidate,
+                };
+            }
+            if lower[i] != query[j] {
+                continue;
+            }
+
+            let consecutive = score[j - 1][i - 1].map(|s| (s + BONUS_CONSECUTIVE, i - 1));
+            let best = match (consecutive, gap_best) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+            if let Some((prev_score, prev_index)) = best {
+                score[j][i] = Some(prev_score + SCORE_MATCH + bonus[i]);
+                from[j][i] = prev_index;
+            }
+        }
+    }
+
+    let (end, best) = (0..n)
+        .filter_map(|i| score[m - 1][i].map(|s| (i, s)))
+        .max_by_key(|(i, s)| (*s, std::cmp::Reverse(*i)))?;
+
+    let mut indices = vec![end; m];
+    for j in (1..m).rev() {
+        indices[j - 1] = from[j][indices[j]];
+    }
+
+    Some(FuzzyMatch {
+        score: best,
+        indices,
+    })
+}
+
+/// Split `text` into (text, is_match) parts for the matched char indices.
+pub fn highlight_indices(text: &str, indices: &[usize]) -> Vec<(String, bool)> {
+    let mut parts: Vec<(String, bool)> = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let is_match = indices.contains(&i);
+        match parts.last_mut() {
+            Some((part, matched)) if *matched == is_match => part.push(c),
+            _ => parts.push((c.to_string(), is_match)),
+        }
+    }
+    parts
+}
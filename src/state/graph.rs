@@ -0,0 +1,121 @@
+use crate::markdown::{extract_links, LinkTarget};
+use crate::state::note::Note;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+}
+
+/// Notes and the links between them. Edges are undirected index pairs into
+/// `nodes`, deduplicated and without self-links.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NoteGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl NoteGraph {
+    pub fn build(notes: &[Note]) -> Self {
+        let nodes: Vec<GraphNode> = notes
+            .iter()
+            .map(|note| GraphNode {
+                id: note.id.clone(),
+                title: note.title.clone(),
+                tags: note.tags(),
+                folder: note.folder.clone(),
+            })
+            .collect();
+
+        let by_id: HashMap<&str, usize> = notes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        let mut by_title: HashMap<String, usize> = HashMap::new();
+        for (i, note) in notes.iter().enumerate() {
+            by_title.entry(note.title.to_lowercase()).or_insert(i);
+        }
+
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+        for (from, note) in notes.iter().enumerate() {
+            for link in extract_links(&note.content) {
+                let to = match &link.target {
+                    LinkTarget::Title(title) => by_title.get(&title.to_lowercase()),
+                    LinkTarget::Id(id) => by_id.get(id.as_str()),
+                };
+                if let Some(&to) = to {
+                    let edge = (from.min(to), from.max(to));
+                    if from != to && seen.insert(edge) {
+                        edges.push(edge);
+                    }
+                }
+            }
+        }
+
+        Self { nodes, edges }
+    }
+
+    /// Keep only the nodes matching `keep` and the edges between them.
+    pub fn filtered(&self, keep: impl Fn(&GraphNode) -> bool) -> Self {
+        let mut remap = HashMap::new();
+        let mut nodes = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if keep(node) {
+                remap.insert(i, nodes.len());
+                nodes.push(node.clone());
+            }
+        }
+
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|(a, b)| Some((*remap.get(a)?, *remap.get(b)?)))
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    /// The note with `id` and its direct neighbors.
+    pub fn local(&self, id: &str) -> Self {
+        let Some(center) = self.nodes.iter().position(|n| n.id == id) else {
+            return Self::default();
+        };
+
+        let mut keep: HashSet<&str> = HashSet::from([id]);
+        for &(a, b) in &self.edges {
+            if a == center {
+                keep.insert(&self.nodes[b].id);
+            } else if b == center {
+                keep.insert(&self.nodes[a].id);
+            }
+        }
+
+        self.filtered(|node| keep.contains(node.id.as_str()))
+    }
+
+    pub fn degree(&self, index: usize) -> usize {
+        self.edges
+            .iter()
+            .filter(|(a, b)| *a == index || *b == index)
+            .count()
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.nodes.iter().flat_map(|n| n.tags.clone()).collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders: Vec<String> = self.nodes.iter().filter_map(|n| n.folder.clone()).collect();
+        folders.sort();
+        folders.dedup();
+        folders
+    }
+}
@@ -0,0 +1,246 @@
+use crate::state::commands::{Command, KeyContext, Shortcut};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// One chord mapped to a command, as stored in the keymap file:
+/// `{ "key": "Ctrl+B", "command": "bold" }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub key: Shortcut,
+    pub command: Command,
+}
+
+/// Two or more commands sharing a chord in the same context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub key: Shortcut,
+    pub commands: Vec<Command>,
+}
+
+/// The user's key bindings. A command's context decides where its bindings
+/// apply, so the same chord can mean one thing in the editor and another
+/// elsewhere. Only the changes from the defaults are stored, so commands
+/// added later still get their default shortcuts:
+/// `{ "bind": [...], "unbind": [...] }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    /// The defaults less `removed`, then `added`
+    bindings: Vec<Binding>,
+    added: Vec<Binding>,
+    removed: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::with_overrides(Vec::new(), Vec::new())
+    }
+}
+
+#[derive(Serialize)]
+struct Overrides<'a> {
+    bind: &'a [Binding],
+    unbind: &'a [Binding],
+}
+
+impl Serialize for Keymap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Overrides {
+            bind: &self.added,
+            unbind: &self.removed,
+        }
+        .serialize(serializer)
+    }
+}
+
+// Entries with unknown commands or unreadable chords are dropped rather than
+// discarding the whole file
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parse = |entries: Option<&serde_json::Value>| -> Vec<Binding> {
+            entries
+                .and_then(|entries| entries.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                .collect()
+        };
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Ok(Self::with_overrides(parse(value.get("bind")), parse(value.get("unbind"))))
+    }
+}
+
+fn default_bindings() -> Vec<Binding> {
+    Command::ALL
+        .iter()
+        .flat_map(|&command| {
+            command
+                .default_shortcuts()
+                .into_iter()
+                .map(move |key| Binding { key, command })
+        })
+        .collect()
+}
+
+impl Keymap {
+    fn with_overrides(added: Vec<Binding>, removed: Vec<Binding>) -> Self {
+        let mut keymap = Self {
+            bindings: Vec::new(),
+            added,
+            removed,
+        };
+        keymap.rebuild();
+        keymap
+    }
+
+    fn rebuild(&mut self) {
+        let mut bindings = default_bindings();
+        bindings.retain(|b| !self.removed.contains(b));
+        bindings.extend(self.added.iter().cloned());
+        self.bindings = bindings;
+    }
+
+    /// The command `key` runs in `context`. Later bindings win, so a
+    /// conflicting chord still does something predictable.
+    pub fn lookup(&self, key: &Shortcut, context: KeyContext) -> Option<Command> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|b| &b.key == key && b.command.context() == context)
+            .map(|b| b.command)
+    }
+
+    pub fn shortcuts(&self, command: Command) -> Vec<Shortcut> {
+        self.bindings
+            .iter()
+            .filter(|b| b.command == command)
+            .map(|b| b.key.clone())
+            .collect()
+    }
+
+    /// Label of the command's first shortcut, for menus and tooltips.
+    pub fn label(&self, command: Command) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|b| b.command == command)
+            .map(|b| b.key.label())
+    }
+
+    /// Button tooltip: the command title with its shortcut, if any.
+    pub fn tooltip(&self, command: Command) -> String {
+        match self.label(command) {
+            Some(label) => format!("{} ({})", command.title(), label),
+            None => command.title().to_string(),
+        }
+    }
+
+    pub fn bind(&mut self, command: Command, key: Shortcut) {
+        let binding = Binding { key, command };
+        if self.bindings.contains(&binding) {
+            return;
+        }
+        if self.removed.contains(&binding) {
+            self.removed.retain(|b| b != &binding);
+        } else {
+            self.added.push(binding);
+        }
+        self.rebuild();
+    }
+
+    pub fn unbind(&mut self, command: Command, key: &Shortcut) {
+        let binding = Binding {
+            key: key.clone(),
+            command,
+        };
+        if self.added.contains(&binding) {
+            self.added.retain(|b| b != &binding);
+        } else if default_bindings().contains(&binding) && !self.removed.contains(&binding) {
+            self.removed.push(binding);
+        }
+        self.rebuild();
+    }
+
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts: Vec<Conflict> = Vec::new();
+        for (i, binding) in self.bindings.iter().enumerate() {
+            let context = binding.command.context();
+            let already_reported = conflicts
+                .iter()
+                .any(|c| c.key == binding.key && c.commands[0].context() == context);
+            if already_reported {
+                continue;
+            }
+
+            let mut commands = vec![binding.command];
+            for other in &self.bindings[i + 1..] {
+                if other.key == binding.key
+                    && other.command.context() == context
+                    && !commands.contains(&other.command)
+                {
+                    commands.push(other.command);
+                }
+            }
+            if commands.len() > 1 {
+                conflicts.push(Conflict {
+                    key: binding.key.clone(),
+                    commands,
+                });
+            }
+        }
+        conflicts
+    }
+
+    /// Commands other than `command` already using `key` in its context.
+    pub fn conflicts_with(&self, command: Command, key: &Shortcut) -> Vec<Command> {
+        self.bindings
+            .iter()
+            .filter(|b| {
+                &b.key == key && b.command != command && b.command.context() == command.context()
+            })
+            .map(|b| b.command)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(text: &str) -> Shortcut {
+        Shortcut::parse(text).unwrap()
+    }
+
+    #[test]
+    fn saves_only_changes_from_the_defaults() {
+        let mut keymap = Keymap::default();
+        assert_eq!(serde_json::to_value(&keymap).unwrap(), json!({ "bind": [], "unbind": [] }));
+
+        keymap.bind(Command::Bold, key("Ctrl+Shift+B"));
+        keymap.unbind(Command::Bold, &key("Ctrl+B"));
+        let saved = serde_json::to_value(&keymap).unwrap();
+        assert_eq!(saved["bind"].as_array().unwrap().len(), 1);
+        assert_eq!(saved["unbind"].as_array().unwrap().len(), 1);
+
+        let loaded: Keymap = serde_json::from_value(saved).unwrap();
+        assert_eq!(loaded, keymap);
+        assert_eq!(loaded.shortcuts(Command::Bold), vec![key("Ctrl+Shift+B")]);
+        assert_eq!(loaded.shortcuts(Command::Italic), Command::Italic.default_shortcuts());
+    }
+
+    #[test]
+    fn undoing_a_change_leaves_no_override() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Command::Bold, key("Alt+B"));
+        keymap.unbind(Command::Bold, &key("Alt+B"));
+        keymap.unbind(Command::Italic, &key("Ctrl+I"));
+        keymap.bind(Command::Italic, key("Ctrl+I"));
+        assert_eq!(keymap, Keymap::default());
+    }
+
+    #[test]
+    fn lookup_respects_context() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.lookup(&key("Ctrl+B"), KeyContext::Editor), Some(Command::Bold));
+        assert_eq!(keymap.lookup(&key("Ctrl+B"), KeyContext::Global), Some(Command::ToggleSidebar));
+        assert_eq!(keymap.lookup(&key("Ctrl+="), KeyContext::Editor), None);
+    }
+}
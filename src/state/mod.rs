@@ -1,5 +1,26 @@
 pub mod app_state;
+pub mod backlinks;
+pub mod commands;
+pub mod daily;
+pub mod fuzzy;
+pub mod graph;
+pub mod keymap;
 pub mod note;
+pub mod query;
+pub mod replace;
+pub mod search;
+pub mod settings;
+pub mod tabs;
+pub mod templates;
+pub mod vim;
 
-pub use app_state::AppState;
+pub use app_state::{AppState, Pane, ViewMode};
+pub use backlinks::{Backlink, BacklinkKind};
+pub use graph::NoteGraph;
+pub use keymap::Keymap;
 pub use note::{NoteSummary, SaveStatus};
+pub use query::QueryError;
+pub use search::SearchHit;
+pub use settings::{DailyNotesSettings, SavedSearch};
+pub use tabs::TabLabel;
+pub use vim::Vim;
@@ -1,7 +1,10 @@
+use crate::markdown::{extract_tags, first_text_line, front_matter_title, opening_heading};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+const TITLE_MAX_CHARS: usize = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Note {
     pub id: String,
@@ -9,6 +12,15 @@ pub struct Note {
     pub content: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub is_template: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Title set in the UI; overrides front matter and the first line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_title: Option<String>,
 }
 
 impl Note {
@@ -20,30 +32,38 @@ impl Note {
             content: String::new(),
             created_at: now,
             updated_at: now,
+            folder: None,
+            is_template: false,
+            pinned: false,
+            custom_title: None,
         }
     }
 
     pub fn extract_title(&mut self) {
-        // Extract title from first markdown heading or first line
+        // Custom title, then front matter `title:`, then the heading the note
+        // opens with, then the first line of text
         let title = self
-            .content
-            .lines()
-            .find(|line| !line.trim().is_empty())
-            .map(|line| {
-                // Remove markdown heading prefix
-                line.trim_start_matches('#').trim().to_string()
-            })
-            .filter(|t| !t.is_empty())
+            .custom_title
+            .clone()
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| front_matter_title(&self.content))
+            .or_else(|| opening_heading(&self.content))
+            .or_else(|| first_text_line(&self.content))
             .unwrap_or_else(|| String::from("Untitled"));
 
         // Truncate if too long
-        self.title = if title.len() > 50 {
-            format!("{}...", &title[..47])
+        self.title = if title.chars().count() > TITLE_MAX_CHARS {
+            let truncated: String = title.chars().take(TITLE_MAX_CHARS - 3).collect();
+            format!("{}...", truncated)
         } else {
             title
         };
     }
 
+    pub fn tags(&self) -> Vec<String> {
+        extract_tags(&self.content)
+    }
+
     pub fn touch(&mut self) {
         self.updated_at = Utc::now();
     }
@@ -59,7 +79,10 @@ impl Default for Note {
 pub struct NoteSummary {
     pub id: String,
     pub title: String,
+    pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub is_template: bool,
+    pub pinned: bool,
 }
 
 impl From<&Note> for NoteSummary {
@@ -67,7 +90,10 @@ impl From<&Note> for NoteSummary {
         Self {
             id: note.id.clone(),
             title: note.title.clone(),
+            created_at: note.created_at,
             updated_at: note.updated_at,
+            is_template: note.is_template,
+            pinned: note.pinned,
         }
     }
 }
@@ -0,0 +1,415 @@
+use crate::state::daily::today;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::fmt;
+
+/// A parsed search query: any of `alternatives` (split by `OR`) matches when
+/// all of its clauses do.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    pub alternatives: Vec<Vec<Clause>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub negated: bool,
+    pub kind: ClauseKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClauseKind {
+    /// Plain words, each matched as a word prefix
+    Text(Vec<String>),
+    /// `"exact phrase"`, lowercased
+    Phrase(String),
+    /// `tag:work`, also matching nested tags like `work/project`
+    Tag(String),
+    /// `in:folder`
+    Folder(String),
+    /// `updated:>2026-01-01`
+    Updated(DateRange),
+    /// `created:last-week`
+    Created(DateRange),
+    /// `has:tasks`
+    Has(HasFilter),
+    /// `is:pinned`
+    Is(IsFilter),
+}
+
+/// Inclusive range of local dates; `None` leaves that side open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HasFilter {
+    Tasks,
+    OpenTasks,
+    Links,
+    Tags,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsFilter {
+    Pinned,
+    Template,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub message: String,
+    /// Character offset of the offending token
+    pub position: usize,
+}
+
+impl QueryError {
+    /// `query` split around the token at fault: the text before it, the
+    /// token and the rest.
+    pub fn split_query<'a>(&self, query: &'a str) -> (&'a str, &'a str, &'a str) {
+        let start = query.char_indices().nth(self.position).map_or(query.len(), |(i, _)| i);
+        let end = query[start..]
+            .find(char::is_whitespace)
+            .map_or(query.len(), |i| start + i);
+        (&query[..start], &query[start..end], &query[end..])
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Query {
+    pub fn is_empty(&self) -> bool {
+        self.alternatives.iter().all(|clauses| clauses.is_empty())
+    }
+
+    /// Whether the query is only words, with no filters, phrases or operators.
+    pub fn is_plain_text(&self) -> bool {
+        self.alternatives.len() <= 1
+            && self
+                .alternatives
+                .iter()
+                .flatten()
+                .all(|c| !c.negated && matches!(c.kind, ClauseKind::Text(_)))
+    }
+
+    /// Words and phrases to highlight in results.
+    pub fn highlight_terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for clause in self.alternatives.iter().flatten().filter(|c| !c.negated) {
+            match &clause.kind {
+                ClauseKind::Text(words) => terms.extend(words.iter().cloned()),
+                ClauseKind::Phrase(phrase) => terms.push(phrase.clone()),
+                _ => {}
+            }
+        }
+        terms
+    }
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+pub fn parse_query(input: &str) -> Result<Query, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut alternatives: Vec<Vec<Clause>> = vec![Vec::new()];
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let negated = chars[i] == '-' && chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+        if negated {
+            i += 1;
+        }
+
+        if chars[i] == '"' {
+            let Some(close) = chars[i + 1..].iter().position(|&c| c == '"') else {
+                return Err(error("Unclosed quote", start));
+            };
+            let phrase: String = chars[i + 1..i + 1 + close].iter().collect();
+            i += close + 2;
+            if !phrase.trim().is_empty() {
+                push_clause(&mut alternatives, negated, ClauseKind::Phrase(phrase.trim().to_lowercase()));
+            }
+            continue;
+        }
+
+        let end = chars[i..]
+            .iter()
+            .position(|c| c.is_whitespace())
+            .map_or(chars.len(), |p| i + p);
+        let token: String = chars[i..end].iter().collect();
+        i = end;
+
+        if token == "OR" && !negated {
+            if alternatives.last().is_some_and(|clauses| clauses.is_empty()) {
+                return Err(error("OR needs a search term before it", start));
+            }
+            alternatives.push(Vec::new());
+            continue;
+        }
+
+        if let Some(kind) = parse_token(&token, start)? {
+            push_clause(&mut alternatives, negated, kind);
+        }
+    }
+
+    if alternatives.len() > 1 && alternatives.last().is_some_and(|clauses| clauses.is_empty()) {
+        return Err(error("OR needs a search term after it", chars.len()));
+    }
+
+    Ok(Query { alternatives })
+}
+
+fn push_clause(alternatives: &mut [Vec<Clause>], negated: bool, kind: ClauseKind) {
+    if let Some(clauses) = alternatives.last_mut() {
+        clauses.push(Clause { negated, kind });
+    }
+}
+
+fn parse_token(token: &str, position: usize) -> Result<Option<ClauseKind>, QueryError> {
+    // Other keys, like in URLs, times or `TODO:`, are plain words
+    if let Some((key, value)) = token.split_once(':')
+        && matches!(key, "tag" | "in" | "updated" | "created" | "has" | "is")
+    {
+        if value.is_empty() {
+            return Err(error(&format!("{}: needs a value", key), position));
+        }
+        let kind = match key {
+            "tag" => ClauseKind::Tag(value.trim_start_matches('#').to_lowercase()),
+            "in" => ClauseKind::Folder(value.to_lowercase()),
+            "updated" => ClauseKind::Updated(parse_date_range(value, position)?),
+            "created" => ClauseKind::Created(parse_date_range(value, position)?),
+            "has" => ClauseKind::Has(match value {
+                "tasks" | "task" => HasFilter::Tasks,
+                "open-tasks" | "todo" => HasFilter::OpenTasks,
+                "links" | "link" => HasFilter::Links,
+                "tags" | "tag" => HasFilter::Tags,
+                _ => {
+                    return Err(error(
+                        &format!("Unknown has:{}, try tasks, open-tasks, links or tags", value),
+                        position,
+                    ));
+                }
+            }),
+            _ => ClauseKind::Is(match value {
+                "pinned" => IsFilter::Pinned,
+                "template" => IsFilter::Template,
+                _ => {
+                    return Err(error(
+                        &format!("Unknown is:{}, try pinned or template", value),
+                        position,
+                    ));
+                }
+            }),
+        };
+        return Ok(Some(kind));
+    }
+
+    let words: Vec<String> = token
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    Ok((!words.is_empty()).then_some(ClauseKind::Text(words)))
+}
+
+fn parse_date_range(value: &str, position: usize) -> Result<DateRange, QueryError> {
+    date_range(value, today(), position)
+}
+
+/// `this-` and `last-` periods are calendar weeks from Monday, months and
+/// years; `7d` is the past 7 days.
+fn date_range(value: &str, today: NaiveDate, position: usize) -> Result<DateRange, QueryError> {
+    let relative_start = |days: i64| DateRange {
+        from: Some(today - Duration::days(days)),
+        to: None,
+    };
+    let since = |from: NaiveDate| DateRange {
+        from: Some(from),
+        to: None,
+    };
+    // From the start of the period before the one starting at `current`
+    let before = |current: NaiveDate, previous: NaiveDate| DateRange {
+        from: Some(previous),
+        to: Some(current - Duration::days(1)),
+    };
+    let week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let month = today.with_day(1).unwrap_or(today);
+    let year = today.with_ordinal(1).unwrap_or(today);
+
+    match value {
+        "this-week" => return Ok(since(week)),
+        "last-week" => return Ok(before(week, week - Duration::days(7))),
+        "this-month" => return Ok(since(month)),
+        "last-month" => {
+            let previous = (month - Duration::days(1)).with_day(1).unwrap_or(month);
+            return Ok(before(month, previous));
+        }
+        "this-year" => return Ok(since(year)),
+        "last-year" => {
+            let previous = (year - Duration::days(1)).with_ordinal(1).unwrap_or(year);
+            return Ok(before(year, previous));
+        }
+        _ => {}
+    }
+    if let Some(days) = value.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+        return Ok(relative_start(days));
+    }
+
+    let (op, rest) = [">=", "<=", ">", "<"]
+        .iter()
+        .find_map(|op| value.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("", value));
+
+    let date = match rest {
+        "today" => today,
+        "yesterday" => today - Duration::days(1),
+        _ => NaiveDate::parse_from_str(rest, "%Y-%m-%d").map_err(|_| {
+            error(
+                &format!("Invalid date {}, use YYYY-MM-DD, today, yesterday, 7d, this-week or last-week", rest),
+                position,
+            )
+        })?,
+    };
+
+    Ok(match op {
+        ">" => DateRange {
+            from: Some(date + Duration::days(1)),
+            to: None,
+        },
+        ">=" => DateRange {
+            from: Some(date),
+            to: None,
+        },
+        "<" => DateRange {
+            from: None,
+            to: Some(date - Duration::days(1)),
+        },
+        "<=" => DateRange {
+            from: None,
+            to: Some(date),
+        },
+        _ => DateRange {
+            from: Some(date),
+            to: Some(date),
+        },
+    })
+}
+
+fn error(message: &str, position: usize) -> QueryError {
+    QueryError {
+        message: message.to_string(),
+        position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clause(negated: bool, kind: ClauseKind) -> Clause {
+        Clause { negated, kind }
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_words_phrases_filters_and_negation() {
+        let query = parse_query(r#"Rust-lang "Exact Phrase" tag:#Work -in:Archive has:todo is:pinned"#).unwrap();
+        assert_eq!(
+            query.alternatives,
+            vec![vec![
+                clause(false, ClauseKind::Text(vec!["rust".into(), "lang".into()])),
+                clause(false, ClauseKind::Phrase("exact phrase".into())),
+                clause(false, ClauseKind::Tag("work".into())),
+                clause(true, ClauseKind::Folder("archive".into())),
+                clause(false, ClauseKind::Has(HasFilter::OpenTasks)),
+                clause(false, ClauseKind::Is(IsFilter::Pinned)),
+            ]]
+        );
+        assert!(!query.is_plain_text());
+        assert_eq!(query.highlight_terms(), vec!["rust", "lang", "exact phrase"]);
+    }
+
+    #[test]
+    fn or_splits_alternatives() {
+        let query = parse_query("a OR b c").unwrap();
+        assert_eq!(query.alternatives.len(), 2);
+        assert_eq!(query.alternatives[1].len(), 2);
+        assert!(parse_query("a or b").unwrap().is_plain_text());
+        assert!(parse_query("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn colons_after_non_filter_keys_are_words() {
+        let query = parse_query("10:30 :x c++:").unwrap();
+        assert!(query.is_plain_text());
+        assert_eq!(query.highlight_terms(), vec!["10", "30", "x", "c"]);
+        assert!(parse_query("https://example.com TODO:fix").unwrap().is_plain_text());
+    }
+
+    #[test]
+    fn date_ranges() {
+        let updated = |text: &str| match &parse_query(text).unwrap().alternatives[0][0].kind {
+            ClauseKind::Updated(range) => *range,
+            kind => panic!("not a date filter: {kind:?}"),
+        };
+        let range = updated("updated:>2026-01-01");
+        assert_eq!((range.from, range.to), (Some(date("2026-01-02")), None));
+        let range = updated("updated:<=2026-01-01");
+        assert_eq!((range.from, range.to), (None, Some(date("2026-01-01"))));
+        let range = updated("updated:2026-01-01");
+        assert!(range.contains(date("2026-01-01")) && !range.contains(date("2026-01-02")));
+        assert_eq!(updated("updated:7d").from, Some(today() - Duration::days(7)));
+    }
+
+    #[test]
+    fn periods_follow_the_calendar() {
+        // A Wednesday
+        let period = |value: &str| {
+            let range = date_range(value, date("2026-03-04"), 0).unwrap();
+            (range.from, range.to)
+        };
+        assert_eq!(period("this-week"), (Some(date("2026-03-02")), None));
+        assert_eq!(period("last-week"), (Some(date("2026-02-23")), Some(date("2026-03-01"))));
+        assert_eq!(period("this-month"), (Some(date("2026-03-01")), None));
+        assert_eq!(period("last-month"), (Some(date("2026-02-01")), Some(date("2026-02-28"))));
+        assert_eq!(period("last-year"), (Some(date("2025-01-01")), Some(date("2025-12-31"))));
+    }
+
+    #[test]
+    fn errors_point_at_the_bad_token() {
+        let cases = [
+            ("a tag:", 2),
+            ("x \"open", 2),
+            ("a OR", 4),
+            ("OR a", 0),
+            ("note has:pictures", 5),
+            ("é updated:soon", 2),
+        ];
+        for (query, position) in cases {
+            let error = parse_query(query).expect_err(query);
+            assert_eq!(error.position, position, "{query}");
+        }
+    }
+
+    #[test]
+    fn split_query_marks_the_token() {
+        let error = parse_query("é has:nope rest").unwrap_err();
+        assert_eq!(error.split_query("é has:nope rest"), ("é ", "has:nope", " rest"));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::state::note::Note;
+use regex::{Regex, RegexBuilder};
+
+const CONTEXT_CHARS: usize = 40;
+
+/// What to find and what to replace it with across the vault.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplaceQuery {
+    pub find: String,
+    pub replace: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// `find` is a regular expression and `replace` may use `$1` groups
+    pub regex: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaceMatch {
+    /// Byte range of the match in the note content
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub matched: String,
+    pub replacement: String,
+    /// Text around the match on its line
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteMatches {
+    pub note_id: String,
+    pub note_title: String,
+    pub matches: Vec<ReplaceMatch>,
+}
+
+impl ReplaceQuery {
+    fn pattern(&self) -> Result<Regex, String> {
+        let mut pattern = if self.regex {
+            self.find.clone()
+        } else {
+            regex::escape(&self.find)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .build()
+            .map_err(|e| match e {
+                regex::Error::Syntax(message) => message.lines().last().unwrap_or("").to_string(),
+                e => e.to_string(),
+            })
+    }
+}
+
+/// Every match of `query` in a single text, in order.
+pub fn find_in_text(content: &str, query: &ReplaceQuery) -> Result<Vec<ReplaceMatch>, String> {
+    if query.find.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(note_matches(&query.pattern()?, content, query))
+}
+
+/// Every match of `query` in `notes`, grouped by note in the given order.
+pub fn find_matches(notes: &[Note], query: &ReplaceQuery) -> Result<Vec<NoteMatches>, String> {
+    if query.find.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pattern = query.pattern()?;
+
+    Ok(notes
+        .iter()
+        .filter_map(|note| {
+            let matches = note_matches(&pattern, &note.content, query);
+            (!matches.is_empty()).then(|| NoteMatches {
+                note_id: note.id.clone(),
+                note_title: note.title.clone(),
+                matches,
+            })
+        })
+        .collect())
+}
+
+fn note_matches(pattern: &Regex, content: &str, query: &ReplaceQuery) -> Vec<ReplaceMatch> {
+    let mut matches = Vec::new();
+    let mut line = 0;
+    let mut counted_to = 0;
+
+    for caps in pattern.captures_iter(content) {
+        let Some(m) = caps.get(0) else { continue };
+        // Empty matches (like `^` or `\b`) would replace nothing useful
+        if m.start() == m.end() {
+            continue;
+        }
+
+        line += content[counted_to..m.start()].matches('\n').count();
+        counted_to = m.start();
+
+        let replacement = if query.regex {
+            let mut expanded = String::new();
+            caps.expand(&query.replace, &mut expanded);
+            expanded
+        } else {
+            query.replace.clone()
+        };
+
+        let line_start = content[..m.start()].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[m.end()..].find('\n').map_or(content.len(), |i| m.end() + i);
+        let before = &content[line_start..m.start()];
+        let after = &content[m.end()..line_end];
+        let before: String = match before.char_indices().rev().nth(CONTEXT_CHARS) {
+            Some((i, c)) => format!("…{}", &before[i + c.len_utf8()..]),
+            None => before.to_string(),
+        };
+        let after: String = match after.char_indices().nth(CONTEXT_CHARS) {
+            Some((i, _)) => format!("{}…", &after[..i]),
+            None => after.to_string(),
+        };
+
+        matches.push(ReplaceMatch {
+            start: m.start(),
+            end: m.end(),
+            line,
+            matched: m.as_str().to_string(),
+            replacement,
+            before: before.trim_start().to_string(),
+            after,
+        });
+    }
+
+    matches
+}
+
+/// `content` with the given matches replaced. Matches must come from
+/// `find_matches` on this exact content.
+pub fn apply_matches<'a>(content: &str, matches: impl IntoIterator<Item = &'a ReplaceMatch>) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut pos = 0;
+    for m in matches {
+        if m.start < pos {
+            continue;
+        }
+        result.push_str(&content[pos..m.start]);
+        result.push_str(&m.replacement);
+        pos = m.end;
+    }
+    result.push_str(&content[pos..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(find: &str, replace: &str) -> ReplaceQuery {
+        ReplaceQuery {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            ..ReplaceQuery::default()
+        }
+    }
+
+    fn replaced(content: &str, query: &ReplaceQuery) -> String {
+        apply_matches(content, &find_in_text(content, query).unwrap())
+    }
+
+    #[test]
+    fn literal_find_is_case_insensitive_by_default() {
+        assert_eq!(replaced("Cat cat c.t", &query("cat", "dog")), "dog dog c.t");
+        assert_eq!(replaced("a.b axb", &query("a.b", "-")), "- axb");
+        let case_sensitive = ReplaceQuery {
+            case_sensitive: true,
+            ..query("cat", "dog")
+        };
+        assert_eq!(replaced("Cat cat", &case_sensitive), "Cat dog");
+    }
+
+    #[test]
+    fn whole_word_skips_parts_of_words() {
+        let whole_word = ReplaceQuery {
+            whole_word: true,
+            ..query("cat", "dog")
+        };
+        assert_eq!(replaced("cat concat cats cat.", &whole_word), "dog concat cats dog.");
+    }
+
+    #[test]
+    fn regex_replacements_expand_groups() {
+        let regex = ReplaceQuery {
+            regex: true,
+            ..query(r"(\w+)@(\w+)", "$2 at $1")
+        };
+        assert_eq!(replaced("me@home", &regex), "home at me");
+        // Empty matches replace nothing
+        let anchors = ReplaceQuery {
+            regex: true,
+            ..query("^", "> ")
+        };
+        assert!(find_in_text("a\nb", &anchors).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_regex_is_an_error() {
+        let regex = ReplaceQuery {
+            regex: true,
+            ..query("(", "")
+        };
+        assert!(find_in_text("(", &regex).is_err());
+        assert!(find_in_text("", &query("", "x")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn matches_know_their_line_and_context() {
+        let content = format!("first\nsecond {} match {}", "x".repeat(50), "y".repeat(50));
+        let matches = find_in_text(&content, &query("match", "")).unwrap();
+        assert_eq!(matches.len(), 1);
+        let m = &matches[0];
+        assert_eq!(m.line, 1);
+        assert_eq!(&content[m.start..m.end], "match");
+        assert!(m.before.starts_with('…') && m.before.chars().count() == CONTEXT_CHARS + 1);
+        assert!(m.after.ends_with('…') && m.after.chars().count() == CONTEXT_CHARS + 1);
+    }
+
+    #[test]
+    fn find_matches_groups_by_note() {
+        let mut notes = vec![Note::new(), Note::new(), Note::new()];
+        notes[0].content = String::from("one cat");
+        notes[1].content = String::from("no match");
+        notes[2].content = String::from("cat\ncat");
+        let found = find_matches(&notes, &query("cat", "")).unwrap();
+        let counts: Vec<(&str, usize)> = found.iter().map(|n| (n.note_id.as_str(), n.matches.len())).collect();
+        assert_eq!(counts, vec![(notes[0].id.as_str(), 1), (notes[2].id.as_str(), 2)]);
+    }
+}
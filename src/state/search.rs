@@ -0,0 +1,328 @@
+use crate::markdown::{extract_links, extract_tasks};
+use crate::state::note::Note;
+use crate::state::query::{Clause, ClauseKind, HasFilter, IsFilter, Query};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const PREFIX_WEIGHT: f64 = 0.7;
+/// Score for a word found inside a title word, like `book` in "Notebook"
+const TITLE_SUBSTRING_SCORE: f64 = 0.5;
+const TITLE_BONUS: f64 = 1.5;
+const PHRASE_BONUS: f64 = 2.0;
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+const SNIPPET_MAX_CHARS: usize = 100;
+const SNIPPET_LEAD_CHARS: usize = 30;
+const MAX_RESULTS: usize = 50;
+
+/// Inverted index over note titles and contents, kept in memory and updated
+/// as notes are saved or deleted.
+#[derive(Default)]
+pub struct SearchIndex {
+    notes: HashMap<String, Note>,
+    /// term -> note id -> occurrences
+    postings: HashMap<String, HashMap<String, u32>>,
+    lengths: HashMap<String, usize>,
+    tags: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    pub updated_at: DateTime<Utc>,
+    pub score: f64,
+    /// Line of the best match, for jumping to it
+    pub line: usize,
+    /// The matching line split into (text, is_match) parts
+    pub snippet: Vec<(String, bool)>,
+}
+
+impl SearchIndex {
+    pub fn build(notes: impl IntoIterator<Item = Note>) -> Self {
+        let mut index = Self::default();
+        for note in notes {
+            index.upsert(&note);
+        }
+        index
+    }
+
+    pub fn upsert(&mut self, note: &Note) {
+        self.remove(&note.id);
+
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        let mut length = 0;
+        for term in tokenize(&note.title).chain(tokenize(&note.content)) {
+            *counts.entry(term).or_default() += 1;
+            length += 1;
+        }
+        for (term, count) in counts {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(note.id.clone(), count);
+        }
+
+        self.lengths.insert(note.id.clone(), length);
+        self.tags.insert(note.id.clone(), note.tags());
+        self.notes.insert(note.id.clone(), note.clone());
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        let Some(old) = self.notes.remove(id) else { return };
+        self.lengths.remove(id);
+        self.tags.remove(id);
+
+        let terms: HashSet<String> = tokenize(&old.title).chain(tokenize(&old.content)).collect();
+        for term in terms {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Note> {
+        self.notes.get(id)
+    }
+
+    pub fn notes(&self) -> impl Iterator<Item = &Note> {
+        self.notes.values()
+    }
+
+    /// The best `MAX_RESULTS` notes matching `query`.
+    pub fn search(&self, query: &Query) -> Vec<SearchHit> {
+        let mut hits = self.search_all(query);
+        hits.truncate(MAX_RESULTS);
+        hits
+    }
+
+    /// Every note matching `query`, best first. Words match as prefixes so
+    /// results show up while typing, and anywhere in a title; filters only
+    /// narrow the results.
+    pub fn search_all(&self, query: &Query) -> Vec<SearchHit> {
+        if query.is_empty() || self.notes.is_empty() {
+            return Vec::new();
+        }
+
+        let mut word_scores: HashMap<&str, HashMap<&str, f64>> = HashMap::new();
+        for clause in query.alternatives.iter().flatten() {
+            if let ClauseKind::Text(words) = &clause.kind {
+                for word in words {
+                    word_scores
+                        .entry(word.as_str())
+                        .or_insert_with(|| self.word_scores(word));
+                }
+            }
+        }
+
+        let terms = query.highlight_terms();
+        let now = Utc::now();
+        let mut hits: Vec<SearchHit> = self
+            .notes
+            .values()
+            .filter_map(|note| {
+                let score = query
+                    .alternatives
+                    .iter()
+                    .filter(|clauses| !clauses.is_empty())
+                    .filter_map(|clauses| self.score_clauses(note, clauses, &word_scores))
+                    .max_by(f64::total_cmp)?;
+
+                let title = note.title.to_lowercase();
+                let title_matches = terms.iter().filter(|t| title.contains(t.as_str())).count();
+                let age_days = now.signed_duration_since(note.updated_at).num_hours() as f64 / 24.0;
+                let recency = 1.0 + 0.5 * (-age_days.max(0.0) / RECENCY_HALF_LIFE_DAYS).exp();
+                let score = (score + title_matches as f64 * TITLE_BONUS) * recency;
+                let (line, snippet) = best_snippet(&note.content, &terms);
+
+                Some(SearchHit {
+                    id: note.id.clone(),
+                    title: note.title.clone(),
+                    updated_at: note.updated_at,
+                    score,
+                    line,
+                    snippet,
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+
+    /// BM25 score per note for indexed words starting with `word`.
+    fn word_scores(&self, word: &str) -> HashMap<&str, f64> {
+        let doc_count = self.notes.len() as f64;
+        let avg_length = (self.lengths.values().sum::<usize>() as f64 / doc_count).max(1.0);
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+
+        for (indexed_term, docs) in self.postings.iter().filter(|(t, _)| t.starts_with(word)) {
+            let df = docs.len() as f64;
+            let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+            let weight = if indexed_term == word { 1.0 } else { PREFIX_WEIGHT };
+            for (id, &tf) in docs {
+                let tf = tf as f64;
+                let length = self.lengths.get(id).copied().unwrap_or(0) as f64;
+                let bm25 = idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / avg_length));
+                let entry = scores.entry(id.as_str()).or_default();
+                *entry = entry.max(bm25 * weight);
+            }
+        }
+
+        scores
+    }
+
+    /// Score of `note` when it satisfies every clause, `None` otherwise.
+    fn score_clauses(
+        &self,
+        note: &Note,
+        clauses: &[Clause],
+        word_scores: &HashMap<&str, HashMap<&str, f64>>,
+    ) -> Option<f64> {
+        let mut total = 0.0;
+        for clause in clauses {
+            let score = self.match_clause(note, &clause.kind, word_scores);
+            if score.is_some() == clause.negated {
+                return None;
+            }
+            if !clause.negated {
+                total += score.unwrap_or(0.0);
+            }
+        }
+        Some(total)
+    }
+
+    fn match_clause(
+        &self,
+        note: &Note,
+        kind: &ClauseKind,
+        word_scores: &HashMap<&str, HashMap<&str, f64>>,
+    ) -> Option<f64> {
+        let tags = self.tags.get(&note.id);
+        let matched = match kind {
+            ClauseKind::Text(words) => {
+                let title = note.title.to_lowercase();
+                return words.iter().try_fold(0.0, |total, word| {
+                    let score = word_scores
+                        .get(word.as_str())
+                        .and_then(|scores| scores.get(note.id.as_str()).copied())
+                        .or_else(|| title.contains(word.as_str()).then_some(TITLE_SUBSTRING_SCORE))?;
+                    Some(total + score)
+                });
+            }
+            ClauseKind::Phrase(phrase) => {
+                let found = note.title.to_lowercase().contains(phrase.as_str())
+                    || note.content.to_lowercase().contains(phrase.as_str());
+                return found.then_some(PHRASE_BONUS);
+            }
+            ClauseKind::Tag(tag) => tags.is_some_and(|tags| {
+                tags.iter()
+                    .any(|t| t == tag || t.strip_prefix(tag.as_str()).is_some_and(|rest| rest.starts_with('/')))
+            }),
+            ClauseKind::Folder(folder) => note
+                .folder
+                .as_deref()
+                .is_some_and(|f| f.to_lowercase() == *folder),
+            ClauseKind::Updated(range) => range.contains(local_date(note.updated_at)),
+            ClauseKind::Created(range) => range.contains(local_date(note.created_at)),
+            ClauseKind::Has(HasFilter::Tasks) => !extract_tasks(&note.content).is_empty(),
+            ClauseKind::Has(HasFilter::OpenTasks) => extract_tasks(&note.content).iter().any(|t| !t.done),
+            ClauseKind::Has(HasFilter::Links) => !extract_links(&note.content).is_empty(),
+            ClauseKind::Has(HasFilter::Tags) => tags.is_some_and(|tags| !tags.is_empty()),
+            ClauseKind::Is(IsFilter::Pinned) => note.pinned,
+            ClauseKind::Is(IsFilter::Template) => note.is_template,
+        };
+        matched.then_some(0.0)
+    }
+}
+
+fn local_date(dt: DateTime<Utc>) -> NaiveDate {
+    dt.with_timezone(&Local).date_naive()
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// The line matching the most terms, with the matches marked.
+fn best_snippet(content: &str, terms: &[String]) -> (usize, Vec<(String, bool)>) {
+    let best = content
+        .lines()
+        .enumerate()
+        .map(|(line_num, line)| {
+            let lower = line.to_lowercase();
+            let matched = terms.iter().filter(|t| lower.contains(t.as_str())).count();
+            (line_num, line, matched)
+        })
+        .filter(|(_, _, matched)| *matched > 0)
+        .max_by(|a, b| a.2.cmp(&b.2).then(b.0.cmp(&a.0)));
+
+    match best {
+        Some((line_num, line, _)) => (line_num, highlight(line.trim(), terms)),
+        // Only the title matched; show the opening line instead
+        None => {
+            let first = content.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            (0, highlight(first.trim(), &[]))
+        }
+    }
+}
+
+pub(crate) fn highlight(line: &str, terms: &[String]) -> Vec<(String, bool)> {
+    let lower = line.to_lowercase();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    // Byte offsets only carry over when lowercasing kept every length
+    if lower.len() == line.len() {
+        for term in terms.iter().filter(|t| !t.is_empty()) {
+            for (start, _) in lower.match_indices(term.as_str()) {
+                let end = start + term.len();
+                if line.is_char_boundary(start) && line.is_char_boundary(end) {
+                    ranges.push((start, end));
+                }
+            }
+        }
+    }
+    ranges.sort();
+
+    let first_match = ranges.first().map_or(0, |r| r.0);
+    let start = line[..first_match]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_LEAD_CHARS)
+        .map_or(0, |(i, _)| i);
+    let end = line[start..]
+        .char_indices()
+        .nth(SNIPPET_MAX_CHARS)
+        .map_or(line.len(), |(i, _)| start + i);
+
+    let mut parts = Vec::new();
+    if start > 0 {
+        parts.push((String::from("…"), false));
+    }
+    let mut pos = start;
+    for (a, b) in ranges {
+        let a = a.max(pos);
+        let b = b.min(end);
+        if a >= b {
+            continue;
+        }
+        if a > pos {
+            parts.push((line[pos..a].to_string(), false));
+        }
+        parts.push((line[a..b].to_string(), true));
+        pos = b;
+    }
+    if pos < end {
+        parts.push((line[pos..end].to_string(), false));
+    }
+    if end < line.len() {
+        parts.push((String::from("…"), false));
+    }
+    parts
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// User preferences persisted next to the notes by the storage backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub daily_notes: DailyNotesSettings,
+    /// Template note used for Ctrl+N, by id
+    pub default_template: Option<String>,
+    /// Searches shown as smart folders in the sidebar, in order
+    pub saved_searches: Vec<SavedSearch>,
+    /// Vim keybindings in the editor
+    pub vim_mode: bool,
+    /// Tabs open when the app last ran, reopened on start
+    pub open_tabs: Vec<OpenTab>,
+    pub active_tab: usize,
+}
+
+/// A tab to reopen, with where the editor was in it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenTab {
+    pub note_id: String,
+    /// Selection in UTF-16 units, as the textarea reports it
+    pub selection: (usize, usize),
+    pub scroll_top: f64,
+}
+
+/// A named search query, e.g. "Open tasks in work notes" for
+/// `has:open-tasks tag:work`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DailyNotesSettings {
+    /// chrono format string used for the daily note title, e.g. `%Y-%m-%d`
+    pub title_format: String,
+    /// Initial content, expanded like a note template
+    pub template: String,
+}
+
+impl Default for DailyNotesSettings {
+    fn default() -> Self {
+        Self {
+            title_format: String::from("%Y-%m-%d"),
+            template: String::from("# {{title}}\n\n"),
+        }
+    }
+}
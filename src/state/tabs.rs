@@ -0,0 +1,86 @@
+use crate::state::note::{Note, SaveStatus};
+
+/// A note open in a tab. For the active tab, `AppState::current_note` and
+/// `AppState::save_status` are the live copies; these fields catch up when
+/// another tab is activated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tab {
+    pub note: Note,
+    pub save_status: SaveStatus,
+    /// Editor selection in UTF-16 units, as the textarea reports it
+    pub selection: (usize, usize),
+    pub scroll_top: f64,
+}
+
+impl Tab {
+    pub fn new(note: Note) -> Self {
+        Self {
+            note,
+            save_status: SaveStatus::Saved,
+            selection: (0, 0),
+            scroll_top: 0.0,
+        }
+    }
+}
+
+/// What the tab bar shows for a tab.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabLabel {
+    pub title: String,
+    pub is_modified: bool,
+}
+
+/// Move the item at `from` to `to`, returning where the item that was at
+/// `tracked` ended up.
+pub fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize, tracked: usize) -> usize {
+    if from >= items.len() || to >= items.len() || from == to {
+        return tracked;
+    }
+    let item = items.remove(from);
+    items.insert(to, item);
+    if tracked == from {
+        to
+    } else if from < tracked && tracked <= to {
+        tracked - 1
+    } else if to <= tracked && tracked < from {
+        tracked + 1
+    } else {
+        tracked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(from: usize, to: usize, tracked: usize) -> (Vec<char>, usize) {
+        let mut items = vec!['a', 'b', 'c', 'd'];
+        let tracked = move_item(&mut items, from, to, tracked);
+        (items, tracked)
+    }
+
+    #[test]
+    fn moves_the_item() {
+        assert_eq!(moved(0, 2, 3).0, vec!['b', 'c', 'a', 'd']);
+        assert_eq!(moved(3, 1, 0).0, vec!['a', 'd', 'b', 'c']);
+    }
+
+    #[test]
+    fn tracked_item_follows_the_move() {
+        // The tracked item is the one moved
+        assert_eq!(moved(1, 3, 1).1, 3);
+        // Moving an earlier item past it shifts it left
+        assert_eq!(moved(0, 2, 2).1, 1);
+        // Moving a later item in front of it shifts it right
+        assert_eq!(moved(3, 0, 1).1, 2);
+        // Moves on the other side leave it alone
+        assert_eq!(moved(2, 3, 0).1, 0);
+    }
+
+    #[test]
+    fn out_of_range_and_same_place_do_nothing() {
+        assert_eq!(moved(4, 0, 1), (vec!['a', 'b', 'c', 'd'], 1));
+        assert_eq!(moved(0, 9, 1), (vec!['a', 'b', 'c', 'd'], 1));
+        assert_eq!(moved(2, 2, 2), (vec!['a', 'b', 'c', 'd'], 2));
+    }
+}
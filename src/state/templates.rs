@@ -0,0 +1,59 @@
+use chrono::{NaiveDate, NaiveTime};
+
+const CURSOR_MARKER: &str = "{{cursor}}";
+const TITLE_MARKER: &str = "{{title}}";
+
+/// Fill in `{{title}}`, `{{date}}` and `{{time}}`, and return where the first
+/// `{{cursor}}` marker was as a byte offset into the expanded content.
+/// Without a title, `{{title}}` is left blank for the user to type, with the
+/// cursor there when the template doesn't place it.
+pub fn expand_template(
+    template: &str,
+    title: Option<&str>,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> (String, Option<usize>) {
+    let mut expanded = template
+        .replace("{{date}}", &date.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &time.format("%H:%M").to_string());
+    expanded = match title {
+        Some(title) => expanded.replace(TITLE_MARKER, title),
+        None if !expanded.contains(CURSOR_MARKER) => {
+            expanded.replacen(TITLE_MARKER, CURSOR_MARKER, 1).replace(TITLE_MARKER, "")
+        }
+        None => expanded.replace(TITLE_MARKER, ""),
+    };
+
+    let cursor = expanded.find(CURSOR_MARKER);
+    (expanded.replace(CURSOR_MARKER, ""), cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(template: &str, title: Option<&str>) -> (String, Option<usize>) {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let time = NaiveTime::from_hms_opt(9, 5, 0).unwrap();
+        expand_template(template, title, date, time)
+    }
+
+    #[test]
+    fn fills_in_date_time_and_title() {
+        assert_eq!(
+            expand("# {{title}}\n{{date}} {{time}}", Some("Plan")),
+            ("# Plan\n2026-03-04 09:05".into(), None)
+        );
+    }
+
+    #[test]
+    fn cursor_marker_gives_the_offset() {
+        assert_eq!(expand("a {{cursor}}b {{cursor}}", None), ("a b ".into(), Some(2)));
+    }
+
+    #[test]
+    fn missing_title_is_where_the_cursor_starts() {
+        assert_eq!(expand("# {{title}}\n{{title}}", None), ("# \n".into(), Some(2)));
+        assert_eq!(expand("# {{title}}\n{{cursor}}", None), ("# \n".into(), Some(3)));
+    }
+}
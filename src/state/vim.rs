@@ -0,0 +1,1532 @@
+use std::collections::HashMap;
+
+const SHIFT_WIDTH: &str = "    ";
+const UNDO_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+}
+
+impl VimMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+            VimMode::VisualLine => "V-LINE",
+        }
+    }
+}
+
+/// What the editor should show after a key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VimOutput {
+    /// New note text, when the key changed it
+    pub content: Option<String>,
+    /// Byte range to select; one character wide for the normal mode cursor
+    pub selection: (usize, usize),
+    /// `:w` was entered
+    pub save: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+/// A change `.` can repeat: the keys that made it and any text typed in
+/// insert mode afterwards.
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    keys: Vec<String>,
+    inserted: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct InsertSession {
+    keys: Vec<String>,
+    content: String,
+    start: usize,
+}
+
+/// Vim emulation for the editor textarea. Keys arrive as `KeyboardEvent`
+/// key names (`"w"`, `"Escape"`, `"<C-r>"`), the text as the current note
+/// content, and the result is the new text and selection to show.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vim {
+    pub mode: VimMode,
+    /// Byte offset of the cursor; in visual modes, the end that moves
+    cursor: usize,
+    /// Where a visual selection started
+    anchor: usize,
+    /// Keys of the command being typed
+    pending: Vec<String>,
+    /// `:` or `/` line being typed, including its first character
+    command_line: Option<String>,
+    registers: HashMap<char, Register>,
+    last_change: Option<Change>,
+    /// Pattern and whether it searches forward
+    last_search: Option<(String, bool)>,
+    /// `f`, `F`, `t` or `T` and the character it looked for
+    last_find: Option<(char, char)>,
+    /// Column `j` and `k` try to keep, in characters
+    column: Option<usize>,
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    insert: Option<InsertSession>,
+}
+
+enum Parse {
+    Incomplete,
+    Invalid,
+    Done(Command),
+}
+
+struct Command {
+    register: Option<char>,
+    count: Option<usize>,
+    action: Action,
+}
+
+enum Action {
+    Motion(MotionKey),
+    /// Operator, count after it and what it applies to
+    Operator(char, Option<usize>, Target),
+    /// Any other command, with the character after it for `r`
+    Simple(String, Option<char>),
+    /// `iw`, `a(`... selecting in visual mode
+    Object(bool, char),
+}
+
+enum Target {
+    Motion(MotionKey),
+    /// The operator doubled: `dd`, `yy`, `>>`
+    Line,
+    /// Inner (`true`) or around text object
+    Object(bool, char),
+}
+
+#[derive(Clone)]
+struct MotionKey {
+    key: String,
+    arg: Option<char>,
+}
+
+struct Motion {
+    to: usize,
+    linewise: bool,
+    inclusive: bool,
+}
+
+const MOTIONS: &[&str] = &[
+    "h", "j", "k", "l", "w", "W", "b", "B", "e", "E", "0", "^", "$", "G", "{", "}", ";", ",", "n", "N", "%", " ",
+    "Backspace", "Enter",
+];
+const OPERATORS: &[&str] = &["d", "c", "y", ">", "<"];
+const SIMPLE: &[&str] = &[
+    "x", "X", "p", "P", "u", "<C-r>", "J", "~", "D", "C", "Y", "s", "S", "o", "O", "i", "a", "I", "A", "v", "V", ".",
+    "/", "?", ":", "*", "Escape",
+];
+/// Commands `.` repeats
+const CHANGES: &[&str] = &["x", "X", "p", "P", "J", "~", "D", "C", "s", "S", "o", "O", "i", "a", "I", "A", "r"];
+
+fn single_char(key: &str) -> Option<char> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn parse(keys: &[String], visual: bool) -> Parse {
+    let mut i = 0;
+    let mut register = None;
+    if keys.first().map(String::as_str) == Some("\"") {
+        match keys.get(1).and_then(|k| single_char(k)) {
+            Some(c) => register = Some(c),
+            None if keys.len() < 2 => return Parse::Incomplete,
+            None => return Parse::Invalid,
+        }
+        i = 2;
+    }
+
+    let (count, next) = parse_count(keys, i);
+    i = next;
+    let Some(key) = keys.get(i) else { return Parse::Incomplete };
+
+    let done = |action| {
+        Parse::Done(Command {
+            register,
+            count,
+            action,
+        })
+    };
+
+    if OPERATORS.contains(&key.as_str()) && !visual {
+        let op = key.chars().next().unwrap_or('d');
+        let (count2, next) = parse_count(keys, i + 1);
+        let Some(target) = keys.get(next) else { return Parse::Incomplete };
+        if target == key {
+            return done(Action::Operator(op, count2, Target::Line));
+        }
+        if target == "i" || target == "a" {
+            return match keys.get(next + 1).and_then(|k| single_char(k)) {
+                Some(c) => done(Action::Operator(op, count2, Target::Object(target == "i", c))),
+                None if keys.len() <= next + 1 => Parse::Incomplete,
+                None => Parse::Invalid,
+            };
+        }
+        return match parse_motion(keys, next) {
+            Some(Some(motion)) => done(Action::Operator(op, count2, Target::Motion(motion))),
+            Some(None) => Parse::Incomplete,
+            None => Parse::Invalid,
+        };
+    }
+
+    if visual && (key == "i" || key == "a") {
+        return match keys.get(i + 1).and_then(|k| single_char(k)) {
+            Some(c) => done(Action::Object(key == "i", c)),
+            None if keys.len() <= i + 1 => Parse::Incomplete,
+            None => Parse::Invalid,
+        };
+    }
+
+    if key == "r" {
+        return match keys.get(i + 1) {
+            Some(k) => match single_char(k) {
+                Some(c) => done(Action::Simple(String::from("r"), Some(c))),
+                None => Parse::Invalid,
+            },
+            None => Parse::Incomplete,
+        };
+    }
+
+    match parse_motion(keys, i) {
+        Some(Some(motion)) => return done(Action::Motion(motion)),
+        Some(None) => return Parse::Incomplete,
+        None => {}
+    }
+
+    if SIMPLE.contains(&key.as_str()) || (visual && OPERATORS.contains(&key.as_str())) {
+        return done(Action::Simple(key.clone(), None));
+    }
+    Parse::Invalid
+}
+
+/// A count starting at `i` and the index after it. `0` alone is a motion.
+fn parse_count(keys: &[String], mut i: usize) -> (Option<usize>, usize) {
+    let mut count: Option<usize> = None;
+    while let Some(digit) = keys.get(i).and_then(|k| single_char(k)).and_then(|c| c.to_digit(10)) {
+        if digit == 0 && count.is_none() {
+            break;
+        }
+        count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize));
+        i += 1;
+    }
+    (count, i)
+}
+
+/// `Some(Some(motion))` when complete, `Some(None)` when it needs more keys.
+fn parse_motion(keys: &[String], i: usize) -> Option<Option<MotionKey>> {
+    let key = keys.get(i)?;
+    let motion = |key: &str, arg| Some(Some(MotionKey { key: key.to_string(), arg }));
+    match key.as_str() {
+        "f" | "F" | "t" | "T" => match keys.get(i + 1) {
+            Some(next) => single_char(next).and_then(|c| motion(key, Some(c))),
+            None => Some(None),
+        },
+        "g" => match keys.get(i + 1).map(String::as_str) {
+            Some("g") => motion("gg", None),
+            Some(_) => None,
+            None => Some(None),
+        },
+        "ArrowLeft" => motion("h", None),
+        "ArrowRight" => motion("l", None),
+        "ArrowUp" => motion("k", None),
+        "ArrowDown" => motion("j", None),
+        "Home" => motion("0", None),
+        "End" => motion("$", None),
+        key if MOTIONS.contains(&key) => motion(key, None),
+        _ => None,
+    }
+}
+
+#[derive(PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punct,
+}
+
+fn class(c: char, big: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if big || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+fn char_at(text: &str, pos: usize) -> Option<char> {
+    text.get(pos..).and_then(|rest| rest.chars().next())
+}
+
+fn next_pos(text: &str, pos: usize) -> usize {
+    char_at(text, pos).map_or(text.len(), |c| pos + c.len_utf8())
+}
+
+fn prev_pos(text: &str, pos: usize) -> usize {
+    text[..pos].chars().next_back().map_or(0, |c| pos - c.len_utf8())
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i)
+}
+
+fn first_non_blank(text: &str, pos: usize) -> usize {
+    let start = line_start(text, pos);
+    let end = line_end(text, pos);
+    text[start..end]
+        .find(|c: char| !c.is_whitespace())
+        .map_or(end, |i| start + i)
+}
+
+/// Start offsets of every line.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect()
+}
+
+fn line_index(text: &str, pos: usize) -> usize {
+    text[..pos].matches('\n').count()
+}
+
+/// Offset of character `column` on the line starting at `start`, kept on the
+/// line's last character.
+fn column_pos(text: &str, start: usize, column: usize) -> usize {
+    let end = line_end(text, start);
+    let last = if end > start { prev_pos(text, end) } else { start };
+    text[start..end].char_indices().nth(column).map_or(last, |(i, _)| start + i)
+}
+
+fn column_of(text: &str, pos: usize) -> usize {
+    text[line_start(text, pos)..pos].chars().count()
+}
+
+/// `pos` moved back onto `text`: inside it and on a character boundary.
+fn clamp_to(text: &str, pos: usize) -> usize {
+    let pos = pos.min(text.len());
+    (0..=pos).rev().find(|&p| text.is_char_boundary(p)).unwrap_or(0)
+}
+
+/// Where the normal mode cursor may rest: on a character, not on a line's
+/// newline unless the line is empty.
+fn clamp_normal(text: &str, pos: usize) -> usize {
+    let pos = clamp_to(text, pos);
+    if (pos == text.len() || text[pos..].starts_with('\n')) && pos > line_start(text, pos) {
+        prev_pos(text, pos)
+    } else {
+        pos
+    }
+}
+
+/// Whole lines covering `start..end`, with the newline after them (or the
+/// one before, for the last line).
+fn expand_lines(text: &str, start: usize, end: usize) -> (usize, usize) {
+    let start = line_start(text, start);
+    let end = line_end(text, end.max(start));
+    if end < text.len() {
+        (start, end + 1)
+    } else if start > 0 {
+        (start - 1, end)
+    } else {
+        (start, end)
+    }
+}
+
+/// One line indented (`>`) or outdented (`<`) by a shift width.
+fn shift_line(line: &str, indent: bool) -> String {
+    if indent {
+        if line.is_empty() { String::new() } else { format!("{SHIFT_WIDTH}{line}") }
+    } else if let Some(rest) = line.strip_prefix('\t') {
+        rest.to_string()
+    } else {
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        line[spaces.min(SHIFT_WIDTH.len())..].to_string()
+    }
+}
+
+impl Vim {
+    /// Mode, command being typed and `:`/`/` line for the status bar.
+    pub fn status(&self) -> String {
+        if let Some(line) = &self.command_line {
+            return line.clone();
+        }
+        if self.pending.is_empty() {
+            self.mode.label().to_string()
+        } else {
+            format!("{} {}", self.mode.label(), self.pending.concat())
+        }
+    }
+
+    /// Start over on another note. Undo history, the cursor and the mode
+    /// belong to the note left behind; registers and the last change,
+    /// search and find carry over, as they do across Vim buffers.
+    pub fn switch_note(&mut self) {
+        *self = Vim {
+            registers: std::mem::take(&mut self.registers),
+            last_change: self.last_change.take(),
+            last_search: self.last_search.take(),
+            last_find: self.last_find.take(),
+            ..Vim::default()
+        };
+    }
+
+    pub fn is_insert(&self) -> bool {
+        self.mode == VimMode::Insert
+    }
+
+    /// Selection to show for the current mode.
+    pub fn selection(&self, text: &str) -> (usize, usize) {
+        let cursor = clamp_to(text, self.cursor);
+        match self.mode {
+            VimMode::Insert => (cursor, cursor),
+            VimMode::Normal => {
+                if self.command_line.is_some() || char_at(text, cursor).is_none_or(|c| c == '\n') {
+                    (cursor, cursor)
+                } else {
+                    (cursor, next_pos(text, cursor))
+                }
+            }
+            VimMode::Visual => {
+                let (start, end) = self.visual_range(text);
+                (start, end)
+            }
+            VimMode::VisualLine => {
+                let anchor = clamp_to(text, self.anchor);
+                let start = line_start(text, anchor.min(cursor));
+                let end = line_end(text, anchor.max(cursor));
+                (start, end)
+            }
+        }
+    }
+
+    fn visual_range(&self, text: &str) -> (usize, usize) {
+        let cursor = clamp_to(text, self.cursor);
+        let anchor = clamp_to(text, self.anchor);
+        let (start, end) = (anchor.min(cursor), anchor.max(cursor));
+        (start, next_pos(text, end).min(text.len()))
+    }
+
+    /// The editor's caret or selection changed by mouse.
+    pub fn set_selection(&mut self, text: &str, start: usize, end: usize) {
+        if self.is_insert() {
+            return;
+        }
+        self.pending.clear();
+        self.column = None;
+        if start == end {
+            if self.mode != VimMode::Normal {
+                self.mode = VimMode::Normal;
+            }
+            self.cursor = clamp_normal(text, start);
+        } else if end > next_pos(text, start) {
+            // Dragged a selection: continue it in visual mode
+            if self.mode == VimMode::Normal {
+                self.mode = VimMode::Visual;
+            }
+            self.anchor = start;
+            self.cursor = prev_pos(text, end);
+        }
+    }
+
+    /// Escape from insert mode, given the textarea's text and caret.
+    pub fn exit_insert(&mut self, text: &str, caret: usize) -> VimOutput {
+        if let Some(session) = self.insert.take() {
+            // Text typed since insert mode started, when it was one insertion
+            let inserted = if caret >= session.start
+                && text.len() >= session.content.len()
+                && text.get(..session.start) == session.content.get(..session.start)
+            {
+                text.get(session.start..caret).unwrap_or("").to_string()
+            } else {
+                String::new()
+            };
+            if !session.keys.is_empty() {
+                self.last_change = Some(Change {
+                    keys: session.keys,
+                    inserted,
+                });
+            }
+            // Nothing changed: drop the undo step taken on entering
+            if text == session.content && self.undo.last().is_some_and(|(saved, _)| saved == text) {
+                self.undo.pop();
+            }
+        }
+        self.mode = VimMode::Normal;
+        let caret = clamp_to(text, caret);
+        self.cursor = if caret > line_start(text, caret) { prev_pos(text, caret) } else { caret };
+        self.cursor = clamp_normal(text, self.cursor);
+        VimOutput {
+            content: None,
+            selection: self.selection(text),
+            save: false,
+        }
+    }
+
+    /// Handle a key outside insert mode. Returns `None` for keys the editor
+    /// should handle itself.
+    pub fn handle_key(&mut self, key: &str, content: &str) -> Option<VimOutput> {
+        if self.is_insert() {
+            return None;
+        }
+        let mut text = content.to_string();
+        // The text may have changed under a stale cursor or visual anchor
+        self.cursor = clamp_to(&text, self.cursor);
+        self.anchor = clamp_to(&text, self.anchor);
+        let mut save = false;
+
+        if self.command_line.is_some() {
+            save = self.command_line_key(key, &text);
+        } else {
+            self.pending.push(key.to_string());
+            let visual = matches!(self.mode, VimMode::Visual | VimMode::VisualLine);
+            match parse(&self.pending, visual) {
+                Parse::Incomplete => {}
+                Parse::Invalid => self.pending.clear(),
+                Parse::Done(command) => {
+                    let keys = std::mem::take(&mut self.pending);
+                    let before = (text.clone(), self.cursor);
+                    let history = matches!(keys.last().map(String::as_str), Some("u" | "<C-r>"));
+                    self.run(command, &keys, &mut text);
+                    // Entering insert mode starts an undo step even before
+                    // anything is typed
+                    if !history && (text != before.0 || self.is_insert()) {
+                        self.undo.push(before);
+                        if self.undo.len() > UNDO_LIMIT {
+                            self.undo.remove(0);
+                        }
+                        self.redo.clear();
+                    }
+                }
+            }
+        }
+
+        if self.mode == VimMode::Normal {
+            self.cursor = clamp_normal(&text, self.cursor);
+        }
+        Some(VimOutput {
+            selection: self.selection(&text),
+            content: (text != content).then_some(text),
+            save,
+        })
+    }
+
+    /// A key typed into the `:` or `/` line. Returns whether to save.
+    fn command_line_key(&mut self, key: &str, text: &str) -> bool {
+        let Some(mut line) = self.command_line.take() else { return false };
+        match key {
+            "Escape" => {}
+            "Backspace" => {
+                line.pop();
+                if !line.is_empty() {
+                    self.command_line = Some(line);
+                }
+            }
+            "Enter" => {
+                let (kind, body) = line.split_at(1);
+                match kind {
+                    ":" => return self.ex_command(body.trim(), text),
+                    _ => {
+                        if !body.is_empty() {
+                            self.last_search = Some((body.to_string(), kind == "/"));
+                        }
+                        if let Some(to) = self.search(text, self.cursor, false, 1) {
+                            self.cursor = to;
+                        }
+                    }
+                }
+            }
+            key => {
+                if let Some(c) = single_char(key) {
+                    line.push(c);
+                }
+                self.command_line = Some(line);
+            }
+        }
+        false
+    }
+
+    fn ex_command(&mut self, command: &str, text: &str) -> bool {
+        match command {
+            "w" | "wq" | "x" | "write" => true,
+            line => {
+                if let Ok(number) = line.parse::<usize>() {
+                    let starts = line_starts(text);
+                    let start = starts[number.saturating_sub(1).min(starts.len() - 1)];
+                    self.cursor = first_non_blank(text, start);
+                }
+                false
+            }
+        }
+    }
+
+    fn run(&mut self, command: Command, keys: &[String], text: &mut String) {
+        let count = command.count.unwrap_or(1);
+        let register = command.register.unwrap_or('"');
+        let visual = matches!(self.mode, VimMode::Visual | VimMode::VisualLine);
+
+        match command.action {
+            Action::Motion(motion) => {
+                if !matches!(motion.key.as_str(), "j" | "k") {
+                    self.column = None;
+                }
+                if let Some(m) = self.motion(text, self.cursor, &motion, command.count, false) {
+                    self.cursor = m.to;
+                }
+            }
+            Action::Object(inner, object) => {
+                if let Some((start, end, _)) = text_object(text, self.cursor, inner, object) {
+                    self.anchor = start;
+                    self.cursor = prev_pos(text, end).max(start);
+                }
+            }
+            Action::Operator(op, count2, target) => {
+                let total = count * count2.unwrap_or(1);
+                let total_count = (command.count.is_some() || count2.is_some()).then_some(total);
+                let range = match target {
+                    Target::Line => {
+                        let starts = line_starts(text);
+                        let line = line_index(text, self.cursor);
+                        let last = (line + total - 1).min(starts.len() - 1);
+                        Some((starts[line], starts[last], true))
+                    }
+                    Target::Object(inner, object) => text_object(text, self.cursor, inner, object),
+                    Target::Motion(mut motion) => {
+                        // `cw` on a word changes to its end, like `ce`
+                        if op == 'c' && char_at(text, self.cursor).is_some_and(|c| !c.is_whitespace()) {
+                            match motion.key.as_str() {
+                                "w" => motion.key = String::from("e"),
+                                "W" => motion.key = String::from("E"),
+                                _ => {}
+                            }
+                        }
+                        self.motion(text, self.cursor, &motion, total_count, true).map(|m| {
+                            let (start, end) = (self.cursor.min(m.to), self.cursor.max(m.to));
+                            let end = if m.inclusive { next_pos(text, end) } else { end };
+                            (start, end, m.linewise)
+                        })
+                    }
+                };
+                if let Some((start, end, linewise)) = range {
+                    self.operate(op, start, end, linewise, register, text);
+                    if op != 'y' {
+                        self.record(keys);
+                    }
+                }
+            }
+            Action::Simple(key, _) if visual => self.visual_command(&key, register, text),
+            Action::Simple(key, arg) => {
+                self.simple(&key, arg, count, register, text);
+                if CHANGES.contains(&key.as_str()) {
+                    self.record(keys);
+                }
+            }
+        }
+    }
+
+    /// Remember `keys` for `.`, waiting for the inserted text when the
+    /// command entered insert mode.
+    fn record(&mut self, keys: &[String]) {
+        if self.is_insert() {
+            if let Some(session) = &mut self.insert {
+                session.keys = keys.to_vec();
+            }
+        } else {
+            self.last_change = Some(Change {
+                keys: keys.to_vec(),
+                inserted: String::new(),
+            });
+        }
+    }
+
+    fn enter_insert(&mut self, text: &str, at: usize) {
+        self.mode = VimMode::Insert;
+        self.cursor = at;
+        self.insert = Some(InsertSession {
+            keys: Vec::new(),
+            content: text.to_string(),
+            start: at,
+        });
+    }
+
+    fn yank(&mut self, register: char, text: String, linewise: bool, is_yank: bool) {
+        let value = Register { text, linewise };
+        if register != '"' && register != '_' {
+            self.registers.insert(register.to_ascii_lowercase(), value.clone());
+        }
+        if register != '_' {
+            if is_yank {
+                self.registers.insert('0', value.clone());
+            }
+            self.registers.insert('"', value);
+        }
+    }
+
+    /// Apply `op` to `start..end`. Linewise ranges cover the lines holding
+    /// `start` and `end`.
+    fn operate(&mut self, op: char, start: usize, end: usize, linewise: bool, register: char, text: &mut String) {
+        if linewise {
+            let first = line_start(text, start);
+            let last = line_end(text, end.max(start));
+            let lines = text[first..last].to_string();
+            let (start, end) = expand_lines(text, start, end);
+            match op {
+                'y' => {
+                    self.yank(register, lines, true, true);
+                    self.cursor = first;
+                }
+                'd' => {
+                    self.yank(register, lines, true, false);
+                    text.replace_range(start..end, "");
+                    self.cursor = first_non_blank(text, start.min(text.len()));
+                }
+                'c' => {
+                    // Keep the first line's indentation to type after
+                    let indent = first_non_blank(text, first);
+                    self.yank(register, lines, true, false);
+                    text.replace_range(indent..last, "");
+                    self.enter_insert(text, indent);
+                }
+                '>' | '<' => {
+                    let shifted: Vec<String> = lines.split('\n').map(|line| shift_line(line, op == '>')).collect();
+                    text.replace_range(first..last, &shifted.join("\n"));
+                    self.cursor = first_non_blank(text, first);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let selected = text[start..end].to_string();
+        match op {
+            'y' => {
+                self.yank(register, selected, false, true);
+                self.cursor = start;
+            }
+            'd' => {
+                self.yank(register, selected, false, false);
+                text.replace_range(start..end, "");
+                self.cursor = start;
+            }
+            'c' => {
+                self.yank(register, selected, false, false);
+                text.replace_range(start..end, "");
+                self.enter_insert(text, start);
+            }
+            '>' | '<' => self.operate(op, start, end, true, register, text),
+            _ => {}
+        }
+    }
+
+    fn simple(&mut self, key: &str, arg: Option<char>, count: usize, register: char, text: &mut String) {
+        let cursor = self.cursor;
+        match key {
+            "i" => self.enter_insert(text, cursor),
+            "a" => {
+                let at = if char_at(text, cursor).is_some_and(|c| c != '\n') { next_pos(text, cursor) } else { cursor };
+                self.enter_insert(text, at);
+            }
+            "I" => self.enter_insert(text, first_non_blank(text, cursor)),
+            "A" => self.enter_insert(text, line_end(text, cursor)),
+            "o" | "O" => {
+                let indent_start = line_start(text, cursor);
+                let indent: String = text[indent_start..]
+                    .chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .collect();
+                let at = if key == "o" {
+                    let end = line_end(text, cursor);
+                    text.insert_str(end, &format!("\n{indent}"));
+                    end + 1 + indent.len()
+                } else {
+                    text.insert_str(indent_start, &format!("{indent}\n"));
+                    indent_start + indent.len()
+                };
+                self.enter_insert(text, at);
+            }
+            "v" => {
+                self.mode = VimMode::Visual;
+                self.anchor = cursor;
+            }
+            "V" => {
+                self.mode = VimMode::VisualLine;
+                self.anchor = cursor;
+            }
+            "x" | "X" | "s" => {
+                let (start, end) = if key == "X" {
+                    let mut start = cursor;
+                    for _ in 0..count {
+                        if start > line_start(text, cursor) {
+                            start = prev_pos(text, start);
+                        }
+                    }
+                    (start, cursor)
+                } else {
+                    let mut end = cursor;
+                    for _ in 0..count {
+                        if char_at(text, end).is_some_and(|c| c != '\n') {
+                            end = next_pos(text, end);
+                        }
+                    }
+                    (cursor, end)
+                };
+                let op = if key == "s" { 'c' } else { 'd' };
+                self.operate(op, start, end, false, register, text);
+            }
+            "D" | "C" => {
+                let end = line_end(text, cursor);
+                let op = if key == "C" { 'c' } else { 'd' };
+                self.operate(op, cursor, end, false, register, text);
+            }
+            "S" => {
+                self.operate('c', cursor, cursor, true, register, text);
+            }
+            "Y" => {
+                self.operate('y', cursor, cursor, true, register, text);
+                self.cursor = cursor;
+            }
+            "p" | "P" => {
+                let Some(value) = self.registers.get(&register.to_ascii_lowercase()).cloned() else { return };
+                if value.linewise {
+                    let pasted = vec![value.text.as_str(); count].join("\n");
+                    let at = if key == "p" {
+                        let end = line_end(text, cursor);
+                        text.insert_str(end, &format!("\n{pasted}"));
+                        end + 1
+                    } else {
+                        let start = line_start(text, cursor);
+                        text.insert_str(start, &format!("{pasted}\n"));
+                        start
+                    };
+                    self.cursor = first_non_blank(text, at);
+                } else {
+                    let at = if key == "p" && char_at(text, cursor).is_some_and(|c| c != '\n') {
+                        next_pos(text, cursor)
+                    } else {
+                        cursor
+                    };
+                    let pasted = value.text.repeat(count);
+                    text.insert_str(at, &pasted);
+                    self.cursor = prev_pos(text, at + pasted.len()).max(at);
+                }
+            }
+            "J" => {
+                for _ in 0..count.max(2) - 1 {
+                    let end = line_end(text, self.cursor);
+                    if end >= text.len() {
+                        break;
+                    }
+                    let next_text = text[end + 1..].len() - text[end + 1..].trim_start_matches([' ', '\t']).len();
+                    let joined_end = end + 1 + next_text;
+                    let separator = if text[..end].ends_with(' ') || joined_end == text.len() || text[joined_end..].starts_with('\n') {
+                        ""
+                    } else {
+                        " "
+                    };
+                    text.replace_range(end..joined_end, separator);
+                    self.cursor = end;
+                }
+            }
+            "~" => {
+                let mut pos = cursor;
+                for _ in 0..count {
+                    let Some(c) = char_at(text, pos).filter(|&c| c != '\n') else { break };
+                    let swapped: String = if c.is_uppercase() {
+                        c.to_lowercase().collect()
+                    } else {
+                        c.to_uppercase().collect()
+                    };
+                    text.replace_range(pos..pos + c.len_utf8(), &swapped);
+                    pos += swapped.len();
+                }
+                self.cursor = pos;
+            }
+            "r" => {
+                let Some(replacement) = arg else { return };
+                let mut end = cursor;
+                for _ in 0..count {
+                    match char_at(text, end) {
+                        Some(c) if c != '\n' => end += c.len_utf8(),
+                        // Not enough characters: vim does nothing
+                        _ => return,
+                    }
+                }
+                let replaced: String = std::iter::repeat_n(replacement, count).collect();
+                text.replace_range(cursor..end, &replaced);
+                self.cursor = cursor + replaced.len() - replacement.len_utf8();
+            }
+            "u" => {
+                for _ in 0..count {
+                    if let Some((saved, at)) = self.undo.pop() {
+                        self.redo.push((std::mem::replace(text, saved), self.cursor));
+                        self.cursor = at;
+                    }
+                }
+            }
+            "<C-r>" => {
+                for _ in 0..count {
+                    if let Some((saved, at)) = self.redo.pop() {
+                        self.undo.push((std::mem::replace(text, saved), self.cursor));
+                        self.cursor = at;
+                    }
+                }
+            }
+            "." => {
+                let Some(change) = self.last_change.clone() else { return };
+                for _ in 0..count {
+                    self.replay(&change, text);
+                }
+            }
+            "/" | "?" | ":" => self.command_line = Some(key.to_string()),
+            "*" => {
+                let (start, end, _) = text_object(text, cursor, true, 'w').unwrap_or((cursor, cursor, false));
+                let word = text[start..end].trim();
+                if !word.is_empty() {
+                    self.last_search = Some((word.to_string(), true));
+                    if let Some(to) = self.search(text, cursor, false, count) {
+                        self.cursor = to;
+                    }
+                }
+            }
+            "Escape" => {}
+            _ => {}
+        }
+    }
+
+    /// Run a recorded change again at the cursor.
+    fn replay(&mut self, change: &Change, text: &mut String) {
+        let last_change = self.last_change.clone();
+        let visual = matches!(self.mode, VimMode::Visual | VimMode::VisualLine);
+        if let Parse::Done(command) = parse(&change.keys, visual) {
+            self.run(command, &change.keys, text);
+        }
+        if self.is_insert() {
+            let at = self.cursor;
+            text.insert_str(at, &change.inserted);
+            self.insert = None;
+            self.mode = VimMode::Normal;
+            let end = at + change.inserted.len();
+            self.cursor = if end > line_start(text, end) { prev_pos(text, end) } else { end };
+        }
+        // Replaying doesn't replace what `.` repeats
+        self.last_change = last_change;
+    }
+
+    fn visual_command(&mut self, key: &str, register: char, text: &mut String) {
+        let linewise = self.mode == VimMode::VisualLine;
+        let (lo, hi) = (self.anchor.min(self.cursor), self.anchor.max(self.cursor));
+        let (start, end) = if linewise { (lo, hi) } else { self.visual_range(text) };
+        // Uppercase commands work on whole lines even in charwise visual
+        let operate = |vim: &mut Self, op, lines: bool, text: &mut String| {
+            vim.mode = VimMode::Normal;
+            if linewise || lines {
+                vim.operate(op, lo, hi, true, register, text);
+            } else {
+                vim.operate(op, start, end, false, register, text);
+            }
+        };
+        match key {
+            "Escape" => self.mode = VimMode::Normal,
+            "v" | "V" => {
+                let mode = if key == "v" { VimMode::Visual } else { VimMode::VisualLine };
+                self.mode = if self.mode == mode { VimMode::Normal } else { mode };
+            }
+            "o" => std::mem::swap(&mut self.anchor, &mut self.cursor),
+            "d" | "x" | "X" | "D" => operate(self, 'd', key == "X" || key == "D", text),
+            "c" | "s" | "S" | "C" => operate(self, 'c', key == "S" || key == "C", text),
+            "y" | "Y" => operate(self, 'y', key == "Y", text),
+            ">" | "<" => operate(self, key.chars().next().unwrap_or('>'), true, text),
+            "~" => {
+                let (start, end) = if linewise { (line_start(text, lo), line_end(text, hi)) } else { (start, end) };
+                let swapped: String = text[start..end]
+                    .chars()
+                    .map(|c| {
+                        if c.is_uppercase() {
+                            c.to_lowercase().collect::<String>()
+                        } else {
+                            c.to_uppercase().collect()
+                        }
+                    })
+                    .collect();
+                text.replace_range(start..end, &swapped);
+                self.mode = VimMode::Normal;
+                self.cursor = start;
+            }
+            "J" => {
+                self.mode = VimMode::Normal;
+                self.cursor = start;
+                let lines = text[start..end].matches('\n').count().max(1) + 1;
+                self.simple("J", None, lines, register, text);
+            }
+            "p" | "P" => {
+                let Some(value) = self.registers.get(&register.to_ascii_lowercase()).cloned() else { return };
+                self.mode = VimMode::Normal;
+                let (start, end) = if linewise { (line_start(text, lo), line_end(text, hi)) } else { (start, end) };
+                let pasted = value.text.as_str();
+                text.replace_range(start..end, pasted);
+                self.cursor = start;
+            }
+            ":" | "/" | "?" => {
+                self.mode = VimMode::Normal;
+                self.command_line = Some(key.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn motion(&mut self, text: &str, from: usize, motion: &MotionKey, count: Option<usize>, operator: bool) -> Option<Motion> {
+        let n = count.unwrap_or(1);
+        let exclusive = |to| Some(Motion { to, linewise: false, inclusive: false });
+        let inclusive = |to| Some(Motion { to, linewise: false, inclusive: true });
+        let starts = line_starts(text);
+
+        match motion.key.as_str() {
+            "h" | "Backspace" => {
+                let start = line_start(text, from);
+                let mut to = from;
+                for _ in 0..n {
+                    if to > start {
+                        to = prev_pos(text, to);
+                    }
+                }
+                exclusive(to)
+            }
+            "l" | " " => {
+                let end = line_end(text, from);
+                let mut to = from;
+                for _ in 0..n {
+                    if to < end {
+                        to = next_pos(text, to);
+                    }
+                }
+                // Only an operator may reach past the last character
+                if !operator && to == end && to > line_start(text, from) {
+                    to = prev_pos(text, to).max(from);
+                }
+                exclusive(to)
+            }
+            "j" | "k" | "Enter" => {
+                let line = line_index(text, from);
+                let target = if motion.key == "k" {
+                    line.checked_sub(n)?
+                } else {
+                    line + n
+                };
+                let start = *starts.get(target)?;
+                let column = *self.column.get_or_insert_with(|| column_of(text, from));
+                let to = if motion.key == "Enter" { first_non_blank(text, start) } else { column_pos(text, start, column) };
+                Some(Motion {
+                    to,
+                    linewise: true,
+                    inclusive: false,
+                })
+            }
+            "0" => exclusive(line_start(text, from)),
+            "^" => exclusive(first_non_blank(text, from)),
+            "$" => {
+                let mut pos = from;
+                for _ in 1..n {
+                    let end = line_end(text, pos);
+                    if end < text.len() {
+                        pos = end + 1;
+                    }
+                }
+                let end = line_end(text, pos);
+                let to = if operator || end == line_start(text, pos) { end } else { prev_pos(text, end) };
+                // `d$` takes the whole line's rest: exclusive of the newline
+                if operator { exclusive(to) } else { inclusive(to) }
+            }
+            "gg" | "G" => {
+                let line = match (count, motion.key.as_str()) {
+                    (Some(line), _) => line.saturating_sub(1).min(starts.len() - 1),
+                    (None, "gg") => 0,
+                    (None, _) => starts.len() - 1,
+                };
+                Some(Motion {
+                    to: first_non_blank(text, starts[line]),
+                    linewise: true,
+                    inclusive: false,
+                })
+            }
+            "w" | "W" => {
+                let big = motion.key == "W";
+                let mut to = from;
+                for _ in 0..n {
+                    to = next_word_start(text, to, big);
+                }
+                // `dw` on a line's last word stops at the end of the line
+                if operator && line_index(text, to) > line_index(text, from) && line_end(text, from) > from {
+                    to = line_end(text, from);
+                }
+                exclusive(to)
+            }
+            "b" | "B" => {
+                let mut to = from;
+                for _ in 0..n {
+                    to = prev_word_start(text, to, motion.key == "B");
+                }
+                exclusive(to)
+            }
+            "e" | "E" => {
+                let mut to = from;
+                for _ in 0..n {
+                    to = word_end(text, to, motion.key == "E");
+                }
+                inclusive(to)
+            }
+            "{" | "}" => {
+                let line = line_index(text, from);
+                let blank = |i: usize| text[starts[i]..line_end(text, starts[i])].trim().is_empty();
+                let mut target = line;
+                for _ in 0..n {
+                    if motion.key == "}" {
+                        target += 1;
+                        while target < starts.len() && !blank(target) {
+                            target += 1;
+                        }
+                        if target >= starts.len() {
+                            return exclusive(text.len());
+                        }
+                    } else {
+                        target = target.saturating_sub(1);
+                        while target > 0 && !blank(target) {
+                            target -= 1;
+                        }
+                    }
+                }
+                exclusive(starts[target])
+            }
+            "f" | "F" | "t" | "T" | ";" | "," => {
+                let (kind, c) = match (motion.key.as_str(), motion.arg) {
+                    (";", _) => self.last_find?,
+                    (",", _) => {
+                        let (kind, c) = self.last_find?;
+                        let reversed = match kind {
+                            'f' => 'F',
+                            'F' => 'f',
+                            't' => 'T',
+                            _ => 't',
+                        };
+                        (reversed, c)
+                    }
+                    (key, Some(c)) => {
+                        let kind = key.chars().next()?;
+                        self.last_find = Some((kind, c));
+                        (kind, c)
+                    }
+                    _ => return None,
+                };
+                let to = find_in_line(text, from, kind, c, n, matches!(motion.key.as_str(), ";" | ","))?;
+                if kind == 'f' || kind == 't' { inclusive(to) } else { exclusive(to) }
+            }
+            "n" | "N" => exclusive(self.search(text, from, motion.key == "N", n)?),
+            "%" => inclusive(matching_bracket(text, from)?),
+            _ => None,
+        }
+    }
+
+    /// The `n`th match of the last search after (or before) `from`, wrapping
+    /// around the note. Lowercase patterns ignore case.
+    fn search(&self, text: &str, from: usize, reverse: bool, n: usize) -> Option<usize> {
+        let (pattern, forward) = self.last_search.clone()?;
+        let forward = forward != reverse;
+        let ignore_case = !pattern.chars().any(char::is_uppercase);
+        let (haystack, needle) = if ignore_case {
+            (text.to_lowercase(), pattern.to_lowercase())
+        } else {
+            (text.to_string(), pattern)
+        };
+        // Lowercasing can change byte lengths; fall back to exact matching
+        let (haystack, needle) = if haystack.len() == text.len() { (haystack, needle) } else { (text.to_string(), needle) };
+        let matches: Vec<usize> = haystack.match_indices(&needle).map(|(i, _)| i).collect();
+        if matches.is_empty() {
+            return None;
+        }
+        let mut pos = from;
+        for _ in 0..n {
+            pos = if forward {
+                *matches.iter().find(|&&m| m > pos).unwrap_or(&matches[0])
+            } else {
+                *matches.iter().rev().find(|&&m| m < pos).unwrap_or(&matches[matches.len() - 1])
+            };
+        }
+        Some(pos)
+    }
+}
+
+fn next_word_start(text: &str, from: usize, big: bool) -> usize {
+    let mut pos = from;
+    if let Some(c) = char_at(text, pos) {
+        let start_class = class(c, big);
+        if start_class != CharClass::Space {
+            while char_at(text, pos).is_some_and(|c| class(c, big) == start_class) {
+                pos = next_pos(text, pos);
+            }
+        }
+    }
+    // Skip whitespace, stopping at an empty line
+    while let Some(c) = char_at(text, pos) {
+        if !c.is_whitespace() {
+            break;
+        }
+        let next = next_pos(text, pos);
+        if c == '\n' && char_at(text, next) == Some('\n') {
+            return next;
+        }
+        pos = next;
+    }
+    pos
+}
+
+fn prev_word_start(text: &str, from: usize, big: bool) -> usize {
+    let mut pos = from;
+    while pos > 0 && char_at(text, prev_pos(text, pos)).is_some_and(char::is_whitespace) {
+        pos = prev_pos(text, pos);
+    }
+    if pos == 0 {
+        return 0;
+    }
+    pos = prev_pos(text, pos);
+    let word_class = char_at(text, pos).map_or(CharClass::Space, |c| class(c, big));
+    while pos > 0 && char_at(text, prev_pos(text, pos)).is_some_and(|c| class(c, big) == word_class) {
+        pos = prev_pos(text, pos);
+    }
+    pos
+}
+
+fn word_end(text: &str, from: usize, big: bool) -> usize {
+    let mut pos = next_pos(text, from);
+    while char_at(text, pos).is_some_and(char::is_whitespace) {
+        pos = next_pos(text, pos);
+    }
+    let Some(c) = char_at(text, pos) else { return prev_pos(text, text.len()) };
+    let word_class = class(c, big);
+    while char_at(text, next_pos(text, pos)).is_some_and(|c| class(c, big) == word_class) {
+        pos = next_pos(text, pos);
+    }
+    pos
+}
+
+/// `f`/`F`/`t`/`T` within the current line. Repeating `t` skips the
+/// character right next to the cursor.
+fn find_in_line(text: &str, from: usize, kind: char, c: char, n: usize, repeat: bool) -> Option<usize> {
+    let start = line_start(text, from);
+    let end = line_end(text, from);
+    let mut pos = from;
+    for i in 0..n {
+        let skip_adjacent = repeat && i == 0 && matches!(kind, 't' | 'T');
+        pos = if kind == 'f' || kind == 't' {
+            let search_from = next_pos(text, if skip_adjacent { next_pos(text, pos) } else { pos });
+            let found = text[search_from.min(end)..end].find(c)? + search_from.min(end);
+            if kind == 't' { prev_pos(text, found) } else { found }
+        } else {
+            let search_to = if skip_adjacent { prev_pos(text, pos) } else { pos };
+            let found = text[start..search_to.max(start)].rfind(c)? + start;
+            if kind == 'T' { next_pos(text, found) } else { found }
+        };
+    }
+    Some(pos)
+}
+
+fn matching_bracket(text: &str, from: usize) -> Option<usize> {
+    let end = line_end(text, from);
+    let (offset, c) = text[from..end].char_indices().find(|(_, c)| "()[]{}".contains(*c))?;
+    let pos = from + offset;
+    let (open, close, forward) = match c {
+        '(' => ('(', ')', true),
+        '[' => ('[', ']', true),
+        '{' => ('{', '}', true),
+        ')' => ('(', ')', false),
+        ']' => ('[', ']', false),
+        _ => ('{', '}', false),
+    };
+    let mut depth = 0i32;
+    if forward {
+        for (i, ch) in text[pos..].char_indices() {
+            depth += (ch == open) as i32 - (ch == close) as i32;
+            if depth == 0 {
+                return Some(pos + i);
+            }
+        }
+    } else {
+        for (i, ch) in text[..=pos].char_indices().rev() {
+            depth += (ch == close) as i32 - (ch == open) as i32;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Range of a text object around `at`: `(start, end, linewise)`.
+fn text_object(text: &str, at: usize, inner: bool, object: char) -> Option<(usize, usize, bool)> {
+    match object {
+        'w' | 'W' => {
+            let big = object == 'W';
+            let c = char_at(text, at).filter(|&c| c != '\n')?;
+            let word_class = class(c, big);
+            let mut start = at;
+            while start > line_start(text, at) && char_at(text, prev_pos(text, start)).is_some_and(|c| class(c, big) == word_class) {
+                start = prev_pos(text, start);
+            }
+            let mut end = next_pos(text, at);
+            while char_at(text, end).is_some_and(|c| c != '\n' && class(c, big) == word_class) {
+                end = next_pos(text, end);
+            }
+            if !inner {
+                // `aw` takes the spaces after the word, or before it at the end
+                let mut spaced = end;
+                while char_at(text, spaced).is_some_and(|c| c == ' ' || c == '\t') {
+                    spaced = next_pos(text, spaced);
+                }
+                if spaced > end {
+                    end = spaced;
+                } else {
+                    while start > line_start(text, at) && char_at(text, prev_pos(text, start)).is_some_and(|c| c == ' ' || c == '\t') {
+                        start = prev_pos(text, start);
+                    }
+                }
+            }
+            Some((start, end, false))
+        }
+        '"' | '\'' | '`' => {
+            let start = line_start(text, at);
+            let end = line_end(text, at);
+            let quotes: Vec<usize> = text[start..end].match_indices(object).map(|(i, _)| start + i).collect();
+            let pair = quotes.chunks(2).find(|pair| pair.len() == 2 && pair[0] <= at && at <= pair[1])
+                .or_else(|| quotes.chunks(2).find(|pair| pair.len() == 2 && pair[0] > at))?;
+            let (open, close) = (pair[0], pair[1]);
+            Some(if inner { (open + 1, close, false) } else { (open, close + 1, false) })
+        }
+        '(' | ')' | 'b' | '[' | ']' | '{' | '}' | 'B' => {
+            let (open, close) = match object {
+                '(' | ')' | 'b' => ('(', ')'),
+                '[' | ']' => ('[', ']'),
+                _ => ('{', '}'),
+            };
+            // Nearest unmatched opener before the cursor
+            let mut depth = 0i32;
+            let mut open_pos = None;
+            let search_end = if char_at(text, at) == Some(open) { next_pos(text, at) } else { at.min(text.len()) };
+            for (i, c) in text[..search_end].char_indices().rev() {
+                if c == close && i != at {
+                    depth += 1;
+                } else if c == open {
+                    if depth == 0 {
+                        open_pos = Some(i);
+                        break;
+                    }
+                    depth -= 1;
+                }
+            }
+            let open_pos = open_pos?;
+            let close_pos = matching_bracket(text, open_pos)?;
+            Some(if inner { (open_pos + 1, close_pos, false) } else { (open_pos, close_pos + 1, false) })
+        }
+        'p' => {
+            let starts = line_starts(text);
+            let line = line_index(text, at);
+            let blank = |i: usize| text[starts[i]..line_end(text, starts[i])].trim().is_empty();
+            let is_blank = blank(line);
+            let mut first = line;
+            while first > 0 && blank(first - 1) == is_blank {
+                first -= 1;
+            }
+            let mut last = line;
+            while last + 1 < starts.len() && blank(last + 1) == is_blank {
+                last += 1;
+            }
+            if !inner {
+                while last + 1 < starts.len() && blank(last + 1) {
+                    last += 1;
+                }
+            }
+            Some((starts[first], starts[last], true))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Editor stand-in: the note text and Vim state keys are fed to.
+    struct Session {
+        vim: Vim,
+        text: String,
+    }
+
+    impl Session {
+        fn new(text: &str) -> Self {
+            Self {
+                vim: Vim::default(),
+                text: text.to_string(),
+            }
+        }
+
+        /// Each character is a key; `<...>` and words after `\` are named keys.
+        fn keys(&mut self, keys: &str) -> &mut Self {
+            for key in split_keys(keys) {
+                let output = self.vim.handle_key(&key, &self.text).expect("key outside insert mode");
+                if let Some(content) = output.content {
+                    self.text = content;
+                }
+            }
+            self
+        }
+
+        /// Type `typed` in insert mode at the caret, then press Escape.
+        fn insert(&mut self, typed: &str) -> &mut Self {
+            assert!(self.vim.is_insert());
+            let caret = self.vim.selection(&self.text).0;
+            self.text.insert_str(caret, typed);
+            self.vim.exit_insert(&self.text, caret + typed.len());
+            self
+        }
+
+        fn cursor(&self) -> usize {
+            self.vim.cursor
+        }
+    }
+
+    fn split_keys(keys: &str) -> Vec<String> {
+        let mut split = Vec::new();
+        let mut chars = keys.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '<' if keys.contains('>') => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                    split.push(format!("<{name}>"));
+                }
+                '\\' => split.push(chars.by_ref().take_while(|&c| c != ' ').collect()),
+                c => split.push(c.to_string()),
+            }
+        }
+        split
+    }
+
+    #[test]
+    fn word_and_line_motions() {
+        let mut session = Session::new("hello big world\nnext");
+        assert_eq!(session.keys("w").cursor(), 6);
+        assert_eq!(session.keys("e").cursor(), 8);
+        assert_eq!(session.keys("b").cursor(), 6);
+        assert_eq!(session.keys("$").cursor(), 14);
+        assert_eq!(session.keys("0").cursor(), 0);
+        assert_eq!(session.keys("j").cursor(), 16);
+        assert_eq!(session.keys("gg").cursor(), 0);
+        assert_eq!(session.keys("fw").cursor(), 10);
+    }
+
+    #[test]
+    fn operators_take_motions_and_counts() {
+        let mut session = Session::new("one two three four");
+        assert_eq!(session.keys("dw").text, "two three four");
+        assert_eq!(session.keys("d2w").text, "four");
+        assert_eq!(session.keys("2x").text, "ur");
+
+        let mut session = Session::new("a\nb\nc");
+        assert_eq!(session.keys("jdd").text, "a\nc");
+        assert_eq!(session.keys("p").text, "a\nc\nb");
+        assert_eq!(session.keys("ggyyP").text, "a\na\nc\nb");
+    }
+
+    #[test]
+    fn change_inner_word_and_repeat() {
+        let mut session = Session::new("red green blue");
+        session.keys("wciw").insert("teal");
+        assert_eq!(session.text, "red teal blue");
+        assert_eq!(session.vim.mode, VimMode::Normal);
+        session.keys("w.");
+        assert_eq!(session.text, "red teal teal");
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut session = Session::new("keep\ndrop");
+        session.keys("jdd");
+        assert_eq!(session.text, "keep");
+        assert_eq!(session.keys("u").text, "keep\ndrop");
+        assert_eq!(session.keys("<C-r>").text, "keep");
+        // Entering and leaving insert mode without typing adds no step
+        session.keys("i").insert("");
+        assert_eq!(session.keys("u").text, "keep\ndrop");
+    }
+
+    #[test]
+    fn visual_modes_select_and_operate() {
+        let mut session = Session::new("hello world");
+        session.keys("ve");
+        assert_eq!(session.vim.selection(&session.text), (0, 5));
+        assert_eq!(session.keys("d").text, " world");
+
+        let mut session = Session::new("a\nb\nc");
+        assert_eq!(session.keys("Vjd").text, "c");
+    }
+
+    #[test]
+    fn search_and_ex_commands() {
+        let mut session = Session::new("one two one two");
+        session.keys("/two\\Enter ");
+        assert_eq!(session.cursor(), 4);
+        assert_eq!(session.keys("n").cursor(), 12);
+        assert_eq!(session.vim.status(), "NORMAL");
+
+        let mut session = Session::new("text");
+        let output = session.keys(":").vim.handle_key("w", "text").unwrap();
+        assert!(!output.save);
+        assert_eq!(session.vim.status(), ":w");
+        assert!(session.vim.handle_key("Enter", "text").unwrap().save);
+    }
+
+    #[test]
+    fn shorter_text_under_a_stale_selection_does_not_panic() {
+        let mut session = Session::new("a long line of text");
+        session.keys("v$");
+        // Another note, or the same one edited elsewhere
+        session.text = String::from("é");
+        assert_eq!(session.vim.selection(&session.text), (0, 2));
+        session.keys("d");
+        assert_eq!(session.text, "");
+
+        let mut session = Session::new("a long line\nand more");
+        session.keys("jV");
+        session.text = String::from("x");
+        assert_eq!(session.vim.selection(&session.text), (0, 1));
+        session.keys("j");
+    }
+
+    #[test]
+    fn switching_notes_keeps_registers_but_not_history() {
+        let mut session = Session::new("copied\nother");
+        session.keys("\"ayyjdd");
+        session.vim.switch_note();
+        session.text = String::from("new note");
+        assert_eq!(session.keys("u").text, "new note");
+        assert_eq!(session.keys("\"ap").text, "new note\ncopied");
+        assert_eq!(session.vim.mode, VimMode::Normal);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::state::note::{Note, NoteSummary};
+use crate::state::keymap::Keymap;
+use crate::state::settings::Settings;
 use crate::storage::traits::StorageBackend;
 use std::fs;
 use std::path::PathBuf;
@@ -6,21 +8,29 @@ use std::path::PathBuf;
 #[derive(Clone)]
 pub struct DesktopStorage {
     notes_dir: PathBuf,
+    settings_path: PathBuf,
+    keymap_path: PathBuf,
 }
 
 impl DesktopStorage {
     pub fn new() -> Self {
-        let notes_dir = dirs::home_dir()
+        let root_dir = dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("."))
-            .join(".mime")
-            .join("notes");
+            .join(".mime");
+        let notes_dir = root_dir.join("notes");
+        let settings_path = root_dir.join("settings.json");
+        let keymap_path = root_dir.join("keymap.json");
 
         // Ensure directory exists
         if !notes_dir.exists() {
             fs::create_dir_all(&notes_dir).ok();
         }
 
-        Self { notes_dir }
+        Self {
+            notes_dir,
+            settings_path,
+            keymap_path,
+        }
     }
 
     fn note_path(&self, id: &str) -> PathBuf {
@@ -41,18 +51,17 @@ impl StorageBackend for DesktopStorage {
         if let Ok(entries) = fs::read_dir(&self.notes_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "json") {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(note) = serde_json::from_str::<Note>(&content) {
-                            summaries.push(NoteSummary::from(&note));
-                        }
-                    }
+                if path.extension().is_some_and(|ext| ext == "json")
+                    && let Ok(content) = fs::read_to_string(&path)
+                    && let Ok(note) = serde_json::from_str::<Note>(&content)
+                {
+                    summaries.push(NoteSummary::from(&note));
                 }
             }
         }
 
         // Sort by updated_at descending
-        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.updated_at));
         summaries
     }
 
@@ -77,4 +86,28 @@ impl StorageBackend for DesktopStorage {
             Ok(())
         }
     }
+
+    fn load_settings(&self) -> Settings {
+        fs::read_to_string(&self.settings_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        fs::write(&self.settings_path, content).map_err(|e| e.to_string())
+    }
+
+    fn load_keymap(&self) -> Keymap {
+        fs::read_to_string(&self.keymap_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_keymap(&self, keymap: &Keymap) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(keymap).map_err(|e| e.to_string())?;
+        fs::write(&self.keymap_path, content).map_err(|e| e.to_string())
+    }
 }
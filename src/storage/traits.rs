@@ -1,8 +1,14 @@
+use crate::state::keymap::Keymap;
 use crate::state::note::{Note, NoteSummary};
+use crate::state::settings::Settings;
 
 pub trait StorageBackend {
     fn list_notes(&self) -> Vec<NoteSummary>;
     fn load_note(&self, id: &str) -> Option<Note>;
     fn save_note(&self, note: &Note) -> Result<(), String>;
     fn delete_note(&self, id: &str) -> Result<(), String>;
+    fn load_settings(&self) -> Settings;
+    fn save_settings(&self, settings: &Settings) -> Result<(), String>;
+    fn load_keymap(&self) -> Keymap;
+    fn save_keymap(&self, keymap: &Keymap) -> Result<(), String>;
 }
@@ -1,8 +1,12 @@
 use crate::state::note::{Note, NoteSummary};
+use crate::state::keymap::Keymap;
+use crate::state::settings::Settings;
 use crate::storage::traits::StorageBackend;
 use gloo_storage::{LocalStorage, Storage};
 
 const NOTES_INDEX_KEY: &str = "mime_notes_index";
+const SETTINGS_KEY: &str = "mime_settings";
+const KEYMAP_KEY: &str = "mime_keymap";
 
 #[derive(Clone)]
 pub struct WebStorage;
@@ -44,7 +48,7 @@ impl StorageBackend for WebStorage {
             .collect();
 
         // Sort by updated_at descending
-        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.updated_at));
         summaries
     }
 
@@ -72,4 +76,20 @@ impl StorageBackend for WebStorage {
         LocalStorage::delete(&Self::note_key(id));
         Ok(())
     }
+
+    fn load_settings(&self) -> Settings {
+        LocalStorage::get(SETTINGS_KEY).unwrap_or_default()
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), String> {
+        LocalStorage::set(SETTINGS_KEY, settings).map_err(|e| e.to_string())
+    }
+
+    fn load_keymap(&self) -> Keymap {
+        LocalStorage::get(KEYMAP_KEY).unwrap_or_default()
+    }
+
+    fn save_keymap(&self, keymap: &Keymap) -> Result<(), String> {
+        LocalStorage::set(KEYMAP_KEY, keymap).map_err(|e| e.to_string())
+    }
 }